@@ -4,19 +4,29 @@ cargo fmt --all
 #Without any ABI. Plain compilation
 cargo build --all --target wasm32-unknown-unknown --release
 
+# Overflow checks disabled, only the overflow examples are loaded from this profile
+cargo build -p overflow -p overflow-secured --target wasm32-unknown-unknown --profile release-unchecked
+
 # Have ABI
 if [ ! -d "res" ]; then
     mkdir -p "res"
 fi
-# if [ ! -d "abi" ]; then
-#     mkdir -p "abi"
-# fi
+if [ ! -d "abi" ]; then
+    mkdir -p "abi"
+fi
 
 # Example
 # cargo near build --manifest-path ./contracts/ft/Cargo.toml --out-dir ./res -r
 # cargo near build --manifest-path ./contracts/exploit/Cargo.toml --out-dir ./res -r
-# cargo near build --manifest-path ./contracts/staking/Cargo.toml --out-dir ./res -r
+cargo near build --manifest-path ./contracts/race-condition/staking/Cargo.toml --out-dir ./res -r
 
-# mv ./res/*.json ./abi
+mv ./res/*.json ./abi
 
 cp ./target/wasm32-unknown-unknown/release/*.wasm ./res
+
+if [ ! -d "res/unchecked" ]; then
+    mkdir -p "res/unchecked"
+fi
+
+cp ./target/wasm32-unknown-unknown/release-unchecked/overflow.wasm ./res/unchecked
+cp ./target/wasm32-unknown-unknown/release-unchecked/overflow_secured.wasm ./res/unchecked
@@ -0,0 +1,59 @@
+use common::{Ownable, Ownership, PauseState, Pausable};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    data: String,
+    pause_state: PauseState,
+    ownership: Ownership,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn init(data: String, owner: AccountId) -> Self {
+        Self {
+            data,
+            pause_state: PauseState::new(b"p"),
+            ownership: Ownership::new(owner),
+        }
+    }
+
+    pub fn get_pause_status(&self) -> bool {
+        self.is_paused()
+    }
+
+    pub fn get_data(&self) -> String {
+        self.when_not_paused();
+        self.data.clone()
+    }
+
+    pub fn pub_toggle_pause(&mut self) {
+        self.assert_owner();
+        self.toggle_pause()
+    }
+}
+
+impl Ownable for StatusMessage {
+    fn ownership(&self) -> &Ownership {
+        &self.ownership
+    }
+
+    fn ownership_mut(&mut self) -> &mut Ownership {
+        &mut self.ownership
+    }
+}
+
+// Without #[near_bindgen] the trait methods are internal. `pause` and `unpause` are only
+// reachable through `pub_toggle_pause`, which checks the owner.
+impl Pausable for StatusMessage {
+    fn pause_state(&self) -> &PauseState {
+        &self.pause_state
+    }
+
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause_state
+    }
+}
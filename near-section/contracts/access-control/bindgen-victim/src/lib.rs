@@ -1,6 +1,4 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-
-use near_sdk::collections::UnorderedSet;
 use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
 
 #[near_bindgen]
@@ -9,7 +7,6 @@ pub struct StatusMessage {
     data: String,
     pause_status: bool,
     owner: AccountId,
-    users: UnorderedSet<AccountId>,
 }
 
 #[near_bindgen]
@@ -17,10 +14,9 @@ impl StatusMessage {
     #[init]
     pub fn init(data: String, owner: AccountId) -> Self {
         Self {
-            owner,
-            users: UnorderedSet::new(b"u".to_vec()),
-            data: data,
+            data,
             pause_status: false,
+            owner,
         }
     }
 
@@ -28,17 +24,6 @@ impl StatusMessage {
         self.pause_status
     }
 
-    pub fn register_user(&mut self, user: AccountId) {
-        self.users.insert(&user);
-        env::log_str(format!("User {} registered", user).as_str());
-    }
-
-    pub fn register_batch(&mut self, users: Vec<AccountId>) {
-        for user in users {
-            self.register_user(user)
-        }
-    }
-
     pub fn get_data(&self) -> String {
         self.when_not_paused();
         self.data.clone()
@@ -51,13 +36,11 @@ impl StatusMessage {
         );
         self.toggle_pause()
     }
+}
 
-    pub fn set_owner(&mut self, new_owner: AccountId) {
-        require!(
-            env::predecessor_account_id() == self.owner,
-            "Only owner can call this function"
-        );
-        self.owner = new_owner;
+impl StatusMessage {
+    fn when_not_paused(&self) {
+        require!(!self.pause_status, "Function is paused");
     }
 }
 
@@ -65,9 +48,11 @@ pub trait Pausable {
     fn toggle_pause(&mut self);
     fn pause(&mut self);
     fn unpause(&mut self);
-    fn when_not_paused(&self);
 }
 
+// VULNERABLE: #[near_bindgen] exports every method of the trait impl as a public contract
+// method. `pause` and `unpause` have no owner check, anyone can call them directly.
+#[near_bindgen]
 impl Pausable for StatusMessage {
     fn toggle_pause(&mut self) {
         if !self.pause_status {
@@ -86,10 +71,4 @@ impl Pausable for StatusMessage {
         self.pause_status = false;
         env::log_str("The system is unpaused")
     }
-
-    fn when_not_paused(&self) {
-        if self.pause_status {
-            env::panic_str("Function is paused")
-        }
-    }
 }
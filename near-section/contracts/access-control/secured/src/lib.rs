@@ -0,0 +1,80 @@
+use common::{Ownable, Ownership, PauseState, Pausable};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    data: String,
+    pause_state: PauseState,
+    ownership: Ownership,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn init(data: String, owner: AccountId) -> Self {
+        Self {
+            data,
+            pause_state: PauseState::new(b"p"),
+            ownership: Ownership::new(owner),
+        }
+    }
+
+    pub fn get_pause_status(&self) -> bool {
+        self.is_paused()
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_get()
+    }
+
+    pub fn get_proposed_owner(&self) -> Option<AccountId> {
+        self.owner_proposed()
+    }
+
+    pub fn get_data(&self) -> String {
+        self.when_not_paused();
+        self.data.clone()
+    }
+
+    pub fn pub_toggle_pause(&mut self) {
+        self.assert_owner();
+        self.toggle_pause()
+    }
+
+    // First step of the ownership handover. The owner only proposes the new owner,
+    // ownership changes once the proposed account accepts it with `accept_owner`.
+    pub fn set_owner(&mut self, new_owner: AccountId) {
+        self.owner_propose(new_owner)
+    }
+
+    // Second step of the ownership handover. Has to be called by the proposed owner itself.
+    pub fn accept_owner(&mut self) {
+        self.owner_accept()
+    }
+
+    pub fn cancel_owner_proposal(&mut self) {
+        self.owner_cancel_proposal()
+    }
+}
+
+impl Ownable for StatusMessage {
+    fn ownership(&self) -> &Ownership {
+        &self.ownership
+    }
+
+    fn ownership_mut(&mut self) -> &mut Ownership {
+        &mut self.ownership
+    }
+}
+
+impl Pausable for StatusMessage {
+    fn pause_state(&self) -> &PauseState {
+        &self.pause_state
+    }
+
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause_state
+    }
+}
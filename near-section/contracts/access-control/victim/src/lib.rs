@@ -1,3 +1,4 @@
+use common::{PauseState, Pausable};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
 
@@ -5,7 +6,7 @@ use near_sdk::{env, near_bindgen, require, AccountId, PanicOnDefault};
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct StatusMessage {
     data: String,
-    pause_status: bool,
+    pause_state: PauseState,
     owner: AccountId,
 }
 
@@ -16,12 +17,12 @@ impl StatusMessage {
         Self {
             owner,
             data: data,
-            pause_status: false,
+            pause_state: PauseState::new(b"p"),
         }
     }
 
     pub fn get_pause_status(&self) -> bool {
-        self.pause_status
+        self.is_paused()
     }
 
     pub fn get_data(&self) -> String {
@@ -46,35 +47,12 @@ impl StatusMessage {
     }
 }
 
-pub trait Pausable {
-    fn toggle_pause(&mut self);
-    fn pause(&mut self);
-    fn unpause(&mut self);
-    fn when_not_paused(&self);
-}
-
 impl Pausable for StatusMessage {
-    fn toggle_pause(&mut self) {
-        if !self.pause_status {
-            self.pause()
-        } else {
-            self.unpause()
-        }
-    }
-
-    fn pause(&mut self) {
-        self.pause_status = true;
-        env::log_str("The system is paused")
-    }
-
-    fn unpause(&mut self) {
-        self.pause_status = false;
-        env::log_str("The system is unpaused")
+    fn pause_state(&self) -> &PauseState {
+        &self.pause_state
     }
 
-    fn when_not_paused(&self) {
-        if self.pause_status {
-            env::panic_str("Function is paused")
-        }
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause_state
     }
 }
@@ -0,0 +1,205 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, LookupSet};
+use near_sdk::{env, log, require, AccountId, IntoStorageKey};
+
+// Admin of every role that has no admin role configured explicitly
+pub const DEFAULT_ADMIN_ROLE: &str = "DEFAULT_ADMIN";
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Roles {
+    members: LookupSet<(String, AccountId)>,
+    admins: LookupMap<String, String>,
+}
+
+impl Roles {
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        let prefix = prefix.into_storage_key();
+
+        Self {
+            members: LookupSet::new([prefix.as_slice(), b"m"].concat()),
+            admins: LookupMap::new([prefix.as_slice(), b"a"].concat()),
+        }
+    }
+
+    // Grants without authorization. Meant for `#[init]` methods seeding the first admin.
+    pub fn grant(&mut self, role: String, account: AccountId) -> bool {
+        self.members.insert(&(role, account))
+    }
+
+    // Revokes without authorization
+    pub fn revoke(&mut self, role: String, account: AccountId) -> bool {
+        self.members.remove(&(role, account))
+    }
+}
+
+pub trait AccessControl {
+    fn roles(&self) -> &Roles;
+    fn roles_mut(&mut self) -> &mut Roles;
+
+    fn acl_has_role(&self, role: &str, account: &AccountId) -> bool {
+        self.roles()
+            .members
+            .contains(&(role.to_string(), account.clone()))
+    }
+
+    fn acl_get_role_admin(&self, role: &str) -> String {
+        self.roles()
+            .admins
+            .get(&role.to_string())
+            .unwrap_or_else(|| DEFAULT_ADMIN_ROLE.to_string())
+    }
+
+    fn assert_role(&self, role: &str) {
+        require!(
+            self.acl_has_role(role, &env::predecessor_account_id()),
+            format!("Missing role {}", role)
+        );
+    }
+
+    fn acl_grant_role(&mut self, role: String, account: AccountId) {
+        self.assert_role(&self.acl_get_role_admin(&role));
+
+        if self.roles_mut().grant(role.clone(), account.clone()) {
+            log!(format!(
+                "Role {} granted to {} by {}",
+                role,
+                account,
+                env::predecessor_account_id()
+            ));
+        }
+    }
+
+    fn acl_revoke_role(&mut self, role: String, account: AccountId) {
+        self.assert_role(&self.acl_get_role_admin(&role));
+
+        if self.roles_mut().revoke(role.clone(), account.clone()) {
+            log!(format!(
+                "Role {} revoked from {} by {}",
+                role,
+                account,
+                env::predecessor_account_id()
+            ));
+        }
+    }
+
+    fn acl_renounce_role(&mut self, role: String) {
+        let account = env::predecessor_account_id();
+
+        require!(
+            self.roles_mut().revoke(role.clone(), account.clone()),
+            "Account does not have the role"
+        );
+
+        log!(format!("Role {} renounced by {}", role, account));
+    }
+
+    fn acl_set_role_admin(&mut self, role: String, admin_role: String) {
+        self.assert_role(&self.acl_get_role_admin(&role));
+
+        log!(format!("Admin role of {} set to {}", role, admin_role));
+
+        self.roles_mut().admins.insert(&role, &admin_role);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const MINTER: &str = "MINTER";
+    const MINTER_ADMIN: &str = "MINTER_ADMIN";
+
+    struct Contract {
+        roles: Roles,
+    }
+
+    impl AccessControl for Contract {
+        fn roles(&self) -> &Roles {
+            &self.roles
+        }
+
+        fn roles_mut(&mut self) -> &mut Roles {
+            &mut self.roles
+        }
+    }
+
+    fn set_predecessor(predecessor: AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .build());
+    }
+
+    fn contract() -> Contract {
+        set_predecessor(accounts(0));
+
+        let mut roles = Roles::new(b"r");
+        roles.grant(DEFAULT_ADMIN_ROLE.to_string(), accounts(0));
+
+        Contract { roles }
+    }
+
+    #[test]
+    fn grant_and_revoke_by_admin() {
+        let mut contract = contract();
+
+        contract.acl_grant_role(MINTER.to_string(), accounts(1));
+        assert!(contract.acl_has_role(MINTER, &accounts(1)));
+        assert!(!contract.acl_has_role(MINTER, &accounts(2)));
+
+        contract.acl_revoke_role(MINTER.to_string(), accounts(1));
+        assert!(!contract.acl_has_role(MINTER, &accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role DEFAULT_ADMIN")]
+    fn grant_by_stranger() {
+        let mut contract = contract();
+        set_predecessor(accounts(1));
+
+        contract.acl_grant_role(MINTER.to_string(), accounts(1));
+    }
+
+    #[test]
+    fn role_admin_delegation() {
+        let mut contract = contract();
+
+        contract.acl_set_role_admin(MINTER.to_string(), MINTER_ADMIN.to_string());
+        contract.acl_grant_role(MINTER_ADMIN.to_string(), accounts(1));
+        assert_eq!(contract.acl_get_role_admin(MINTER), MINTER_ADMIN);
+
+        set_predecessor(accounts(1));
+        contract.acl_grant_role(MINTER.to_string(), accounts(2));
+        assert!(contract.acl_has_role(MINTER, &accounts(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role MINTER_ADMIN")]
+    fn default_admin_loses_delegated_role() {
+        let mut contract = contract();
+
+        contract.acl_set_role_admin(MINTER.to_string(), MINTER_ADMIN.to_string());
+        contract.acl_grant_role(MINTER.to_string(), accounts(1));
+    }
+
+    #[test]
+    fn renounce_role() {
+        let mut contract = contract();
+        contract.acl_grant_role(MINTER.to_string(), accounts(1));
+
+        set_predecessor(accounts(1));
+        contract.acl_renounce_role(MINTER.to_string());
+
+        assert!(!contract.acl_has_role(MINTER, &accounts(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account does not have the role")]
+    fn renounce_missing_role() {
+        let mut contract = contract();
+        set_predecessor(accounts(1));
+
+        contract.acl_renounce_role(MINTER.to_string());
+    }
+}
@@ -0,0 +1,15 @@
+//! Security primitives shared by the PoC contracts and their fixed variants.
+//!
+//! Every primitive keeps its state in a plain struct that the contract stores as a field,
+//! and exposes its behaviour through a trait with default methods. A contract only has to
+//! implement the accessors to get the whole component. `Payout` has no state of its own, it
+//! only needs a way to credit the contract's ledger.
+pub mod access_control;
+pub mod ownable;
+pub mod pausable;
+pub mod payout;
+
+pub use access_control::{AccessControl, Roles, DEFAULT_ADMIN_ROLE};
+pub use ownable::{Ownable, Ownership, OWNER_PROPOSAL_EXPIRY};
+pub use pausable::{PauseState, Pausable};
+pub use payout::Payout;
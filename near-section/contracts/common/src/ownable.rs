@@ -0,0 +1,216 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, log, require, AccountId, BlockHeight};
+
+// Roughly one day worth of blocks. A proposal that is not accepted in time has to be made again.
+pub const OWNER_PROPOSAL_EXPIRY: BlockHeight = 86_400;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Ownership {
+    owner: AccountId,
+    // Proposed owner and the block height after which the proposal can no longer be accepted
+    proposed_owner: Option<(AccountId, BlockHeight)>,
+}
+
+impl Ownership {
+    pub fn new(owner: AccountId) -> Self {
+        Self {
+            owner,
+            proposed_owner: None,
+        }
+    }
+}
+
+pub trait Ownable {
+    fn ownership(&self) -> &Ownership;
+    fn ownership_mut(&mut self) -> &mut Ownership;
+
+    fn owner_get(&self) -> AccountId {
+        self.ownership().owner.clone()
+    }
+
+    fn owner_proposed(&self) -> Option<AccountId> {
+        self.ownership()
+            .proposed_owner
+            .as_ref()
+            .map(|(proposed_owner, _)| proposed_owner.clone())
+    }
+
+    // Authorizes the immediate caller. Unlike `env::signer_account_id()` it can not be
+    // inherited by a contract the owner was tricked into calling.
+    fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.ownership().owner,
+            "Only owner can call this function"
+        );
+    }
+
+    // Transfers ownership right away. Prefer the two-step `owner_propose`/`owner_accept` flow
+    // when a typo in `new_owner` would lock the contract.
+    fn owner_set(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+
+        log!(format!(
+            "Ownership transferred from {} to {}",
+            self.ownership().owner,
+            new_owner
+        ));
+
+        let ownership = self.ownership_mut();
+        ownership.owner = new_owner;
+        ownership.proposed_owner = None;
+    }
+
+    // First step of the ownership handover. The owner only proposes the new owner,
+    // ownership changes once the proposed account accepts it with `owner_accept`.
+    fn owner_propose(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        require!(
+            new_owner != self.ownership().owner,
+            "Account is already the owner"
+        );
+
+        let expires_at = env::block_height() + OWNER_PROPOSAL_EXPIRY;
+
+        log!(format!(
+            "Ownership proposed to {} until block {}",
+            new_owner, expires_at
+        ));
+
+        self.ownership_mut().proposed_owner = Some((new_owner, expires_at));
+    }
+
+    // Second step of the ownership handover. Has to be called by the proposed owner itself.
+    fn owner_accept(&mut self) {
+        let proposal = self.ownership_mut().proposed_owner.take();
+
+        require!(proposal.is_some(), "No ownership proposal");
+
+        let (proposed_owner, expires_at) = proposal.unwrap();
+
+        require!(
+            env::predecessor_account_id() == proposed_owner,
+            "Only proposed owner can call this function"
+        );
+        require!(
+            env::block_height() <= expires_at,
+            "Ownership proposal expired"
+        );
+
+        log!(format!(
+            "Ownership transferred from {} to {}",
+            self.ownership().owner,
+            proposed_owner
+        ));
+
+        self.ownership_mut().owner = proposed_owner;
+    }
+
+    fn owner_cancel_proposal(&mut self) {
+        self.assert_owner();
+        self.ownership_mut().proposed_owner = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    struct Contract {
+        ownership: Ownership,
+    }
+
+    impl Ownable for Contract {
+        fn ownership(&self) -> &Ownership {
+            &self.ownership
+        }
+
+        fn ownership_mut(&mut self) -> &mut Ownership {
+            &mut self.ownership
+        }
+    }
+
+    fn set_context(predecessor: AccountId, block_height: BlockHeight) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .block_index(block_height)
+            .build());
+    }
+
+    fn contract() -> Contract {
+        Contract {
+            ownership: Ownership::new(accounts(0)),
+        }
+    }
+
+    #[test]
+    fn owner_set_by_owner() {
+        let mut contract = contract();
+        set_context(accounts(0), 0);
+
+        contract.owner_set(accounts(1));
+
+        assert_eq!(contract.owner_get(), accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this function")]
+    fn owner_set_by_stranger() {
+        let mut contract = contract();
+        set_context(accounts(1), 0);
+
+        contract.owner_set(accounts(1));
+    }
+
+    #[test]
+    fn owner_two_step_handover() {
+        let mut contract = contract();
+        set_context(accounts(0), 10);
+
+        contract.owner_propose(accounts(1));
+
+        assert_eq!(contract.owner_get(), accounts(0));
+        assert_eq!(contract.owner_proposed(), Some(accounts(1)));
+
+        set_context(accounts(1), 10 + OWNER_PROPOSAL_EXPIRY);
+        contract.owner_accept();
+
+        assert_eq!(contract.owner_get(), accounts(1));
+        assert_eq!(contract.owner_proposed(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only proposed owner can call this function")]
+    fn owner_accept_by_stranger() {
+        let mut contract = contract();
+        set_context(accounts(0), 0);
+        contract.owner_propose(accounts(1));
+
+        set_context(accounts(2), 0);
+        contract.owner_accept();
+    }
+
+    #[test]
+    #[should_panic(expected = "Ownership proposal expired")]
+    fn owner_accept_expired() {
+        let mut contract = contract();
+        set_context(accounts(0), 10);
+        contract.owner_propose(accounts(1));
+
+        set_context(accounts(1), 11 + OWNER_PROPOSAL_EXPIRY);
+        contract.owner_accept();
+    }
+
+    #[test]
+    #[should_panic(expected = "No ownership proposal")]
+    fn owner_accept_cancelled() {
+        let mut contract = contract();
+        set_context(accounts(0), 0);
+        contract.owner_propose(accounts(1));
+        contract.owner_cancel_proposal();
+
+        set_context(accounts(1), 0);
+        contract.owner_accept();
+    }
+}
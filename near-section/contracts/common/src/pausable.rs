@@ -0,0 +1,259 @@
+use crate::Ownable;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedSet;
+use near_sdk::{env, log, require, AccountId, IntoStorageKey};
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PauseState {
+    // Pauses the whole contract
+    pause_status: bool,
+    // Pauses single methods by name
+    paused_methods: UnorderedSet<String>,
+    // Account that may pause, but never unpause
+    guardian: Option<AccountId>,
+}
+
+impl PauseState {
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        Self {
+            pause_status: false,
+            paused_methods: UnorderedSet::new(prefix),
+            guardian: None,
+        }
+    }
+}
+
+// Internal pausing logic. It does not authorize the caller, the contract decides who may
+// reach these methods (e.g. `pub_toggle_pause` guarded by `Ownable::assert_owner`).
+pub trait Pausable {
+    fn pause_state(&self) -> &PauseState;
+    fn pause_state_mut(&mut self) -> &mut PauseState;
+
+    fn is_paused(&self) -> bool {
+        self.pause_state().pause_status
+    }
+
+    // A method is paused on its own or together with the whole contract
+    fn is_method_paused(&self, method: &str) -> bool {
+        self.is_paused()
+            || self
+                .pause_state()
+                .paused_methods
+                .contains(&method.to_string())
+    }
+
+    fn paused_methods(&self) -> Vec<String> {
+        self.pause_state().paused_methods.to_vec()
+    }
+
+    fn pause_guardian(&self) -> Option<AccountId> {
+        self.pause_state().guardian.clone()
+    }
+
+    fn set_pause_guardian(&mut self, guardian: Option<AccountId>) {
+        log!(format!("Pause guardian set to {:?}", guardian));
+        self.pause_state_mut().guardian = guardian;
+    }
+
+    // The owner may pause and unpause, the guardian may only pause
+    fn assert_can_pause(&self)
+    where
+        Self: Ownable,
+    {
+        let caller = env::predecessor_account_id();
+
+        require!(
+            caller == self.owner_get() || self.pause_guardian() == Some(caller),
+            "Only owner or pause guardian can call this function"
+        );
+    }
+
+    fn toggle_pause(&mut self) {
+        if !self.is_paused() {
+            self.pause()
+        } else {
+            self.unpause()
+        }
+    }
+
+    fn pause(&mut self) {
+        self.pause_state_mut().pause_status = true;
+        env::log_str("The system is paused")
+    }
+
+    fn unpause(&mut self) {
+        self.pause_state_mut().pause_status = false;
+        env::log_str("The system is unpaused")
+    }
+
+    fn pause_method(&mut self, method: String) {
+        if self.pause_state_mut().paused_methods.insert(&method) {
+            log!(format!("Method {} is paused", method));
+        }
+    }
+
+    fn unpause_method(&mut self, method: String) {
+        if self.pause_state_mut().paused_methods.remove(&method) {
+            log!(format!("Method {} is unpaused", method));
+        }
+    }
+
+    fn when_not_paused(&self) {
+        require!(!self.is_paused(), "Function is paused");
+    }
+
+    fn when_method_not_paused(&self, method: &str) {
+        require!(
+            !self.is_method_paused(method),
+            format!("Method {} is paused", method)
+        );
+    }
+
+    // Guard for emergency-only methods that must not run during normal operation
+    fn when_paused(&self) {
+        require!(self.is_paused(), "Function is not paused");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ownership;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    struct Contract {
+        ownership: Ownership,
+        pause_state: PauseState,
+    }
+
+    impl Ownable for Contract {
+        fn ownership(&self) -> &Ownership {
+            &self.ownership
+        }
+
+        fn ownership_mut(&mut self) -> &mut Ownership {
+            &mut self.ownership
+        }
+    }
+
+    impl Pausable for Contract {
+        fn pause_state(&self) -> &PauseState {
+            &self.pause_state
+        }
+
+        fn pause_state_mut(&mut self) -> &mut PauseState {
+            &mut self.pause_state
+        }
+    }
+
+    fn set_predecessor(predecessor: AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .build());
+    }
+
+    fn contract() -> Contract {
+        set_predecessor(accounts(0));
+
+        Contract {
+            ownership: Ownership::new(accounts(0)),
+            pause_state: PauseState::new(b"p"),
+        }
+    }
+
+    #[test]
+    fn toggle_pause() {
+        let mut contract = contract();
+
+        assert!(!contract.is_paused());
+
+        contract.toggle_pause();
+        assert!(contract.is_paused());
+
+        contract.toggle_pause();
+        assert!(!contract.is_paused());
+    }
+
+    #[test]
+    fn when_not_paused_passes_while_unpaused() {
+        let mut contract = contract();
+
+        contract.pause();
+        contract.unpause();
+        contract.when_not_paused();
+    }
+
+    #[test]
+    #[should_panic(expected = "Function is paused")]
+    fn when_not_paused_panics_while_paused() {
+        let mut contract = contract();
+
+        contract.pause();
+        contract.when_not_paused();
+    }
+
+    #[test]
+    fn pause_single_method() {
+        let mut contract = contract();
+
+        contract.pause_method("register_user".to_string());
+
+        assert!(contract.is_method_paused("register_user"));
+        assert!(!contract.is_method_paused("get_data"));
+        assert!(!contract.is_paused());
+        assert_eq!(contract.paused_methods(), vec!["register_user".to_string()]);
+
+        contract.unpause_method("register_user".to_string());
+
+        assert!(!contract.is_method_paused("register_user"));
+        assert!(contract.paused_methods().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Method get_data is paused")]
+    fn global_pause_covers_every_method() {
+        let mut contract = contract();
+
+        contract.pause();
+        contract.when_method_not_paused("get_data");
+    }
+
+    #[test]
+    fn when_paused_passes_while_paused() {
+        let mut contract = contract();
+
+        contract.pause();
+        contract.when_paused();
+    }
+
+    #[test]
+    #[should_panic(expected = "Function is not paused")]
+    fn when_paused_panics_while_unpaused() {
+        let contract = contract();
+
+        contract.when_paused();
+    }
+
+    #[test]
+    fn guardian_can_pause() {
+        let mut contract = contract();
+        contract.set_pause_guardian(Some(accounts(1)));
+
+        set_predecessor(accounts(1));
+        contract.assert_can_pause();
+
+        set_predecessor(accounts(0));
+        contract.assert_can_pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or pause guardian can call this function")]
+    fn stranger_can_not_pause() {
+        let mut contract = contract();
+        contract.set_pause_guardian(Some(accounts(1)));
+
+        set_predecessor(accounts(2));
+        contract.assert_can_pause();
+    }
+}
@@ -0,0 +1,65 @@
+use near_sdk::{json_types::U128, log, AccountId, PromiseError};
+
+// Payouts debit the ledger before the transfer, so the same balance can not be spent twice
+// while the transfer is in flight. A failed transfer, e.g. to a deleted account, refunds the
+// contract, and the callback of the payout hands its result to `resolve_payout`.
+pub trait Payout {
+    // Credits a failed payout back to the ledger
+    fn payout_refund(&mut self, account: AccountId, amount: U128);
+
+    // Whether the payout went through. A failed one is re-credited to `account`.
+    fn resolve_payout(
+        &mut self,
+        call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        if call_result.is_err() {
+            log!(format!(
+                "Payout of {} for {} failed. Restoring balance",
+                amount.0, account
+            ));
+
+            self.payout_refund(account, amount);
+            false
+        } else {
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct Contract {
+        balances: HashMap<AccountId, u128>,
+    }
+
+    impl Payout for Contract {
+        fn payout_refund(&mut self, account: AccountId, amount: U128) {
+            *self.balances.entry(account).or_default() += amount.0;
+        }
+    }
+
+    #[test]
+    fn successful_payout_is_kept() {
+        let mut contract = Contract::default();
+
+        assert!(contract.resolve_payout(Ok(()), accounts(0), U128(10)));
+        assert!(!contract.balances.contains_key(&accounts(0)));
+    }
+
+    #[test]
+    fn failed_payout_is_restored() {
+        let mut contract = Contract::default();
+
+        contract.balances.insert(accounts(0), 5);
+
+        assert!(!contract.resolve_payout(Err(PromiseError::Failed), accounts(0), U128(10)));
+        assert_eq!(contract.balances.get(&accounts(0)), Some(&15));
+    }
+}
@@ -0,0 +1,426 @@
+use common::{Ownable, Ownership, PauseState, Pausable};
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, serde_json::json,
+    AccountId, Balance, Gas, PanicOnDefault, Promise, StorageUsage,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+// Upper bound on users accepted by a single `register_batch` call
+pub const MAX_BATCH_SIZE: usize = 50;
+
+// Gas kept aside after the loop to charge storage and emit the event
+pub const BATCH_GAS_RESERVE: Gas = Gas(10 * TGAS);
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    data: String,
+    pause_state: PauseState,
+    ownership: Ownership,
+    users: UnorderedSet<AccountId>,
+    // Account that paid the storage of every registered user
+    registered_by: LookupMap<AccountId, AccountId>,
+    // NEAR deposited by every account to pay for the storage its calls write (NEP-145)
+    storage_balances: LookupMap<AccountId, StorageBalance>,
+    // Bytes taken by a single `storage_balances` entry, covered by the minimum storage balance
+    account_storage_usage: StorageUsage,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn init(data: String, owner: AccountId) -> Self {
+        let mut this = Self {
+            ownership: Ownership::new(owner),
+            users: UnorderedSet::new(b"u".to_vec()),
+            registered_by: LookupMap::new(b"r".to_vec()),
+            data,
+            pause_state: PauseState::new(b"p"),
+            storage_balances: LookupMap::new(b"s".to_vec()),
+            account_storage_usage: 0,
+        };
+
+        this.measure_account_storage_usage();
+        this
+    }
+
+    pub fn get_pause_status(&self) -> bool {
+        self.is_paused()
+    }
+
+    pub fn get_paused_methods(&self) -> Vec<String> {
+        self.paused_methods()
+    }
+
+    pub fn get_method_pause_status(&self, method: String) -> bool {
+        self.is_method_paused(&method)
+    }
+
+    pub fn get_pause_guardian(&self) -> Option<AccountId> {
+        self.pause_guardian()
+    }
+
+    // Registration is paid by the caller. The storage written is measured and charged
+    // from the caller's storage balance, so spamming users costs the attacker, not the contract.
+    pub fn register_user(&mut self, user: AccountId) {
+        self.when_method_not_paused("register_user");
+
+        let caller = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+
+        require!(self.users.insert(&user), "User already registered");
+        self.registered_by.insert(&user, &caller);
+
+        let storage_cost = Balance::from(env::storage_usage() - initial_storage_usage)
+            * env::storage_byte_cost();
+
+        self.charge_storage(&caller, storage_cost);
+
+        env::log_str(format!("User {} registered", user).as_str());
+    }
+
+    // Registers up to `MAX_BATCH_SIZE` users. Stops before running out of gas and returns the
+    // index of the first user that was not processed, the rest has to be sent in a new call.
+    // Already registered users are skipped. Emits a single event for the whole batch.
+    pub fn register_batch(&mut self, users: Vec<AccountId>) -> Option<u32> {
+        self.when_method_not_paused("register_batch");
+
+        require!(
+            users.len() <= MAX_BATCH_SIZE,
+            format!("Batch size exceeds {}", MAX_BATCH_SIZE)
+        );
+
+        let caller = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+
+        let mut registered = 0;
+        let mut cursor = None;
+        // Most expensive iteration so far, used as the estimate for the next one
+        let mut iteration_gas = Gas(0);
+
+        for (index, user) in users.iter().enumerate() {
+            let remaining_gas = env::prepaid_gas() - env::used_gas();
+
+            if remaining_gas < iteration_gas + BATCH_GAS_RESERVE {
+                cursor = Some(index as u32);
+                break;
+            }
+
+            let used_gas_before = env::used_gas();
+
+            if self.users.insert(user) {
+                self.registered_by.insert(user, &caller);
+                registered += 1;
+            }
+
+            iteration_gas = iteration_gas.max(env::used_gas() - used_gas_before);
+        }
+
+        let storage_cost = Balance::from(env::storage_usage() - initial_storage_usage)
+            * env::storage_byte_cost();
+
+        self.charge_storage(&caller, storage_cost);
+
+        env::log_str(&format!(
+            "EVENT_JSON:{}",
+            json!({
+                "standard": "status_message",
+                "version": "1.0.0",
+                "event": "register_batch",
+                "data": [{
+                    "registered": registered,
+                    "processed": cursor.unwrap_or(users.len() as u32),
+                    "next_cursor": cursor,
+                }]
+            })
+        ));
+
+        cursor
+    }
+
+    pub fn get_users_count(&self) -> u64 {
+        self.users.len()
+    }
+
+    pub fn get_data(&self) -> String {
+        self.when_not_paused();
+        self.data.clone()
+    }
+
+    // Emergency-only: clean up registrations while the contract is paused. The storage released
+    // is credited back to the account that paid for the registration.
+    pub fn remove_user(&mut self, user: AccountId) -> bool {
+        self.assert_owner();
+        self.when_paused();
+
+        let initial_storage_usage = env::storage_usage();
+
+        if !self.users.remove(&user) {
+            return false;
+        }
+
+        let payer = self.registered_by.remove(&user);
+
+        let storage_refund = Balance::from(initial_storage_usage - env::storage_usage())
+            * env::storage_byte_cost();
+
+        if let Some(payer) = payer {
+            self.release_storage(&payer, storage_refund);
+        }
+
+        true
+    }
+
+    pub fn pub_toggle_pause(&mut self) {
+        self.assert_owner();
+        self.toggle_pause()
+    }
+
+    pub fn pub_pause(&mut self) {
+        self.assert_can_pause();
+        self.pause()
+    }
+
+    pub fn pub_unpause(&mut self) {
+        self.assert_owner();
+        self.unpause()
+    }
+
+    pub fn pub_pause_method(&mut self, method: String) {
+        self.assert_can_pause();
+        self.pause_method(method)
+    }
+
+    pub fn pub_unpause_method(&mut self, method: String) {
+        self.assert_owner();
+        self.unpause_method(method)
+    }
+
+    pub fn pub_set_pause_guardian(&mut self, guardian: Option<AccountId>) {
+        self.assert_owner();
+        self.set_pause_guardian(guardian)
+    }
+
+    pub fn set_owner(&mut self, new_owner: AccountId) {
+        self.owner_set(new_owner)
+    }
+}
+
+impl StatusMessage {
+    fn measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = AccountId::new_unchecked("a".repeat(64));
+
+        self.storage_balances.insert(
+            &tmp_account_id,
+            &StorageBalance {
+                total: U128(0),
+                available: U128(0),
+            },
+        );
+
+        self.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.storage_balances.remove(&tmp_account_id);
+    }
+
+    fn charge_storage(&mut self, account_id: &AccountId, storage_cost: Balance) {
+        let mut balance = self
+            .storage_balances
+            .get(account_id)
+            .unwrap_or_else(|| env::panic_str("Account is not registered"));
+
+        balance.available = U128::from(
+            balance
+                .available
+                .0
+                .checked_sub(storage_cost)
+                .unwrap_or_else(|| env::panic_str("Not enough storage deposit")),
+        );
+
+        self.storage_balances.insert(account_id, &balance);
+
+        log!(format!(
+            "Charged {} for storage of {}. Available: {}",
+            storage_cost, account_id, balance.available.0
+        ));
+    }
+
+    // Returns released storage to the used part of the balance of `account_id`. Never credits
+    // more than the account has in use. An account that unregistered with `force` gave up
+    // its used storage, the contract keeps it.
+    fn release_storage(&mut self, account_id: &AccountId, storage_refund: Balance) {
+        let mut balance = match self.storage_balances.get(account_id) {
+            Some(balance) => balance,
+            None => {
+                log!(format!(
+                    "{} is not registered, storage of {} is kept",
+                    account_id, storage_refund
+                ));
+                return;
+            }
+        };
+
+        let min_balance = self.storage_balance_bounds().min.0;
+        let used = balance.total.0 - balance.available.0 - min_balance;
+
+        balance.available = U128::from(balance.available.0 + storage_refund.min(used));
+
+        self.storage_balances.insert(account_id, &balance);
+
+        log!(format!(
+            "Released {} for storage of {}. Available: {}",
+            storage_refund, account_id, balance.available.0
+        ));
+    }
+}
+
+#[near_bindgen]
+impl StorageManagement for StatusMessage {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min_balance = self.storage_balance_bounds().min.0;
+
+        let balance = if let Some(mut balance) = self.storage_balances.get(&account_id) {
+            if registration_only {
+                log!("The account is already registered, refunding the deposit");
+
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+            } else {
+                balance.total = U128::from(balance.total.0 + amount);
+                balance.available = U128::from(balance.available.0 + amount);
+            }
+
+            balance
+        } else {
+            require!(
+                amount >= min_balance,
+                "The attached deposit is less than the minimum storage balance"
+            );
+
+            let total = if registration_only {
+                let refund = amount - min_balance;
+
+                if refund > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+
+                min_balance
+            } else {
+                amount
+            };
+
+            StorageBalance {
+                total: U128::from(total),
+                available: U128::from(total - min_balance),
+            }
+        };
+
+        self.storage_balances.insert(&account_id, &balance);
+        balance
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+
+        let account_id = env::predecessor_account_id();
+        let mut balance = self
+            .storage_balances
+            .get(&account_id)
+            .unwrap_or_else(|| env::panic_str("Account is not registered"));
+
+        let amount = amount.unwrap_or(balance.available);
+
+        require!(
+            amount <= balance.available,
+            "The amount is greater than the available storage balance"
+        );
+
+        balance.total = U128::from(balance.total.0 - amount.0);
+        balance.available = U128::from(balance.available.0 - amount.0);
+
+        self.storage_balances.insert(&account_id, &balance);
+
+        if amount.0 > 0 {
+            Promise::new(account_id).transfer(amount.0);
+        }
+
+        balance
+    }
+
+    // Registered users stay after unregistering. With `force` the storage they use is kept
+    // by the contract, only the available balance and the minimum balance are refunded.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+
+        let account_id = env::predecessor_account_id();
+        let force = force.unwrap_or(false);
+
+        if let Some(balance) = self.storage_balances.get(&account_id) {
+            let min_balance = self.storage_balance_bounds().min.0;
+            let used = balance.total.0 - balance.available.0 - min_balance;
+
+            require!(
+                used == 0 || force,
+                "Can't unregister the account with used storage without force"
+            );
+
+            self.storage_balances.remove(&account_id);
+
+            Promise::new(account_id).transfer(balance.available.0 + min_balance);
+
+            true
+        } else {
+            log!(format!("The account {} is not registered", account_id));
+            false
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128::from(Balance::from(self.account_storage_usage) * env::storage_byte_cost()),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_balances.get(&account_id)
+    }
+}
+
+impl Ownable for StatusMessage {
+    fn ownership(&self) -> &Ownership {
+        &self.ownership
+    }
+
+    fn ownership_mut(&mut self) -> &mut Ownership {
+        &mut self.ownership
+    }
+}
+
+impl Pausable for StatusMessage {
+    fn pause_state(&self) -> &PauseState {
+        &self.pause_state
+    }
+
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause_state
+    }
+}
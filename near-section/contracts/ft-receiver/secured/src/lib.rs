@@ -0,0 +1,115 @@
+use common::Payout;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    assert_one_yocto, collections::LookupMap, env, ext_contract, json_types::U128, log,
+    near_bindgen, require, AccountId, Gas, PanicOnDefault, Promise, PromiseError, PromiseOrValue,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[ext_contract(fungible_token)]
+trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+// Vault for a single NEP-141 token. Deposits arrive through `ft_transfer_call`.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct TokenVault {
+    token: AccountId,
+    balances: LookupMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl TokenVault {
+    #[init]
+    pub fn new(token: AccountId) -> Self {
+        Self {
+            token,
+            balances: LookupMap::new(b"b"),
+        }
+    }
+
+    #[payable]
+    pub fn withdraw(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+
+        let caller = env::predecessor_account_id();
+        let balance = self.view_balance(caller.clone());
+
+        require!(amount.0 != 0, "Amount should not be 0");
+        require!(amount <= balance, "Not enough balance");
+
+        self.balances.insert(&caller, &U128(balance.0 - amount.0));
+
+        fungible_token::ext(self.token.clone())
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(1)
+            .ft_transfer(caller.clone(), amount, None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_withdraw(caller, amount),
+            )
+    }
+
+    #[private]
+    pub fn on_withdraw(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        self.resolve_payout(call_result, account, amount)
+    }
+
+    pub fn get_token(&self) -> AccountId {
+        self.token.clone()
+    }
+
+    pub fn view_balance(&self, acc: AccountId) -> U128 {
+        self.balances.get(&acc).unwrap_or(U128(0))
+    }
+}
+
+impl Payout for TokenVault {
+    fn payout_refund(&mut self, account: AccountId, amount: U128) {
+        let balance = self.view_balance(account.clone());
+
+        self.balances.insert(&account, &U128(balance.0 + amount.0));
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for TokenVault {
+    // Only the token contract itself can report a transfer. `sender_id` and `amount` are
+    // trusted because they come from the token's own `ft_transfer_call`.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        require!(
+            env::predecessor_account_id() == self.token,
+            "Only the vault token is accepted"
+        );
+
+        let balance = self.view_balance(sender_id.clone());
+
+        self.balances
+            .insert(&sender_id, &U128(balance.0 + amount.0));
+
+        log!(format!(
+            "Deposited {} for {} from {}. Message: {}",
+            amount.0,
+            sender_id,
+            env::predecessor_account_id(),
+            msg
+        ));
+
+        // Nothing to refund
+        PromiseOrValue::Value(U128(0))
+    }
+}
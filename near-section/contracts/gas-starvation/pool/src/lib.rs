@@ -0,0 +1,32 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, json_types::U128, log, near_bindgen, AccountId, PanicOnDefault};
+
+// Remote side of the flow. It only keeps the NEAR it receives, so it is cheap to call.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Pool {
+    total_received: U128,
+}
+
+#[near_bindgen]
+impl Pool {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            total_received: U128(0),
+        }
+    }
+
+    #[payable]
+    pub fn deposit_and_stake(&mut self, beneficiary: AccountId) {
+        let amount = env::attached_deposit();
+
+        self.total_received = U128(self.total_received.0 + amount);
+
+        log!(format!("Received {} for {}", amount, beneficiary));
+    }
+
+    pub fn get_total_received(&self) -> U128 {
+        self.total_received
+    }
+}
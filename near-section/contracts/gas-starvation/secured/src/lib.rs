@@ -0,0 +1,162 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, ext_contract, json_types::U128, log, near_bindgen, require,
+    AccountId, Gas, PanicOnDefault, Promise, PromiseError, ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+// Upper bound on validators in a single `stake`, which bounds the cost of `resolve_stake`
+pub const MAX_VALIDATORS: usize = 100;
+
+// Gas for the pool call. The pool does a fixed amount of work.
+pub const POOL_GAS: Gas = Gas(3 * TGAS);
+
+// Gas of `resolve_stake` without validators, and for every validator it writes
+pub const RESOLVE_BASE_GAS: Gas = Gas(5 * TGAS);
+pub const RESOLVE_GAS_PER_VALIDATOR: Gas = Gas(TGAS / 2);
+
+// Gas `stake` burns itself, including the creation of both promises
+pub const STAKE_GAS: Gas = Gas(15 * TGAS);
+
+#[ext_contract(pool)]
+trait Pool {
+    fn deposit_and_stake(&mut self, beneficiary: AccountId);
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StakeSplitter {
+    user_near: LookupMap<AccountId, U128>,
+    // (beneficiary, validator) -> stake
+    stakes: LookupMap<(AccountId, AccountId), U128>,
+    pool: AccountId,
+}
+
+#[near_bindgen]
+impl StakeSplitter {
+    #[init]
+    pub fn new(pool: AccountId) -> Self {
+        Self {
+            user_near: LookupMap::new(b"u"),
+            stakes: LookupMap::new(b"s"),
+            pool,
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 + deposit));
+
+        log!(format!("Added {} for {}", deposit, caller));
+    }
+
+    // Sends `amount` to the pool and splits it evenly between `validators` in the ledger.
+    // The prepaid gas has to cover the callback for the given number of validators, and
+    // all unused gas goes to the callback, never to the pool.
+    // The deposit is debited before the pool call, so concurrent stakes can not spend it twice.
+    // `resolve_stake` re-credits it if the pool call fails.
+    pub fn stake(&mut self, validators: Vec<AccountId>, amount: U128) -> Promise {
+        let beneficiary = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(beneficiary.clone());
+
+        require!(!validators.is_empty(), "No validators");
+        require!(amount.0 != 0, "Amount should not be 0");
+        require!(amount <= near_deposit, "Not enough money");
+        require!(
+            validators.len() <= MAX_VALIDATORS,
+            format!("More than {} validators", MAX_VALIDATORS)
+        );
+
+        let resolve_gas =
+            RESOLVE_BASE_GAS + Gas(RESOLVE_GAS_PER_VALIDATOR.0 * validators.len() as u64);
+
+        require!(
+            env::prepaid_gas() >= STAKE_GAS + POOL_GAS + resolve_gas,
+            "Not enough gas attached"
+        );
+
+        self.user_near
+            .insert(&beneficiary, &U128(near_deposit.0 - amount.0));
+
+        pool::ext(self.pool.clone())
+            .with_static_gas(POOL_GAS)
+            .with_unused_gas_weight(0)
+            .with_attached_deposit(amount.0)
+            .deposit_and_stake(beneficiary.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(resolve_gas)
+                    .with_unused_gas_weight(1)
+                    .resolve_stake(beneficiary, validators, amount),
+            )
+    }
+
+    #[private]
+    pub fn resolve_stake(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        beneficiary: AccountId,
+        validators: Vec<AccountId>,
+        amount: U128,
+    ) -> bool {
+        if let Err(err) = call_result {
+            log!(format!(
+                "ERROR STAKING: {:?}. Restoring {} for {}",
+                err, amount.0, beneficiary
+            ));
+
+            let near_deposit = self.view_near_deposit(beneficiary.clone());
+
+            self.user_near
+                .insert(&beneficiary, &U128(near_deposit.0 + amount.0));
+
+            return false;
+        }
+
+        self.split_stake(&beneficiary, &validators, amount);
+
+        true
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near.get(&acc).unwrap_or(U128(0))
+    }
+
+    pub fn view_stake(&self, beneficiary: AccountId, validator: AccountId) -> U128 {
+        self.stakes
+            .get(&(beneficiary, validator))
+            .unwrap_or(U128(0))
+    }
+}
+
+impl StakeSplitter {
+    // Remainder of the division goes to the first validator
+    fn split_stake(&mut self, beneficiary: &AccountId, validators: &[AccountId], amount: U128) {
+        let share = amount.0 / validators.len() as u128;
+        let remainder = amount.0 % validators.len() as u128;
+
+        for (index, validator) in validators.iter().enumerate() {
+            let key = (beneficiary.clone(), validator.clone());
+            let stake = self.stakes.get(&key).unwrap_or(U128(0));
+            let added = if index == 0 { share + remainder } else { share };
+
+            self.stakes.insert(&key, &U128(stake.0 + added));
+        }
+
+        log!(format!(
+            "Staked {} by {} across {} validators",
+            amount.0,
+            beneficiary,
+            validators.len()
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, ext_contract, json_types::U128, log, near_bindgen, require,
+    AccountId, Gas, PanicOnDefault, Promise, PromiseError, ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[ext_contract(pool)]
+trait Pool {
+    fn deposit_and_stake(&mut self, beneficiary: AccountId);
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StakeSplitter {
+    user_near: LookupMap<AccountId, U128>,
+    // (beneficiary, validator) -> stake
+    stakes: LookupMap<(AccountId, AccountId), U128>,
+    pool: AccountId,
+}
+
+#[near_bindgen]
+impl StakeSplitter {
+    #[init]
+    pub fn new(pool: AccountId) -> Self {
+        Self {
+            user_near: LookupMap::new(b"u"),
+            stakes: LookupMap::new(b"s"),
+            pool,
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 + deposit));
+
+        log!(format!("Added {} for {}", deposit, caller));
+    }
+
+    // Sends `amount` to the pool and splits it evenly between `validators` in the ledger.
+    //
+    // VULNERABLE: both calls get a fixed 3 TGas plus a share of the unused gas. The callback
+    // does the bookkeeping and its cost grows with `validators`. With prepaid gas just above
+    // what this method needs nothing is left over: the pool accepts the NEAR, the callback
+    // runs out of gas and the deposit is never debited.
+    pub fn stake(&mut self, validators: Vec<AccountId>, amount: U128) -> Promise {
+        let beneficiary = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(beneficiary.clone());
+
+        require!(!validators.is_empty(), "No validators");
+        require!(amount.0 != 0, "Amount should not be 0");
+        require!(amount <= near_deposit, "Not enough money");
+
+        pool::ext(self.pool.clone())
+            .with_static_gas(Gas(3 * TGAS))
+            .with_attached_deposit(amount.0)
+            .deposit_and_stake(beneficiary.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas(3 * TGAS))
+                    .resolve_stake(beneficiary, validators, amount),
+            )
+    }
+
+    #[private]
+    pub fn resolve_stake(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        beneficiary: AccountId,
+        validators: Vec<AccountId>,
+        amount: U128,
+    ) -> bool {
+        if let Err(err) = call_result {
+            log!(format!("ERROR STAKING: {:?}", err));
+            return false;
+        }
+
+        let near_deposit = self.view_near_deposit(beneficiary.clone());
+
+        self.user_near
+            .insert(&beneficiary, &U128(near_deposit.0 - amount.0));
+
+        self.split_stake(&beneficiary, &validators, amount);
+
+        true
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near.get(&acc).unwrap_or(U128(0))
+    }
+
+    pub fn view_stake(&self, beneficiary: AccountId, validator: AccountId) -> U128 {
+        self.stakes
+            .get(&(beneficiary, validator))
+            .unwrap_or(U128(0))
+    }
+}
+
+impl StakeSplitter {
+    // Remainder of the division goes to the first validator
+    fn split_stake(&mut self, beneficiary: &AccountId, validators: &[AccountId], amount: U128) {
+        let share = amount.0 / validators.len() as u128;
+        let remainder = amount.0 % validators.len() as u128;
+
+        for (index, validator) in validators.iter().enumerate() {
+            let key = (beneficiary.clone(), validator.clone());
+            let stake = self.stakes.get(&key).unwrap_or(U128(0));
+            let added = if index == 0 { share + remainder } else { share };
+
+            self.stakes.insert(&key, &U128(stake.0 + added));
+        }
+
+        log!(format!(
+            "Staked {} by {} across {} validators",
+            amount.0,
+            beneficiary,
+            validators.len()
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    env, json_types::U128, log, near_bindgen, require, AccountId, PanicOnDefault, Promise,
+};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Treasury {
+    owner: AccountId,
+}
+
+#[near_bindgen]
+impl Treasury {
+    // Only the contract account can initialize. Deploying and initializing in one batch
+    // transaction closes the window as well, see `init_front_running_batch` in the tests.
+    #[private]
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        log!(format!(
+            "Initialized by {} with owner {}",
+            env::predecessor_account_id(),
+            owner
+        ));
+
+        Self { owner }
+    }
+
+    #[payable]
+    pub fn fund(&mut self) {
+        log!(format!(
+            "Funded by {} with {}",
+            env::predecessor_account_id(),
+            env::attached_deposit()
+        ));
+    }
+
+    pub fn withdraw(&mut self, amount: U128) -> Promise {
+        require!(
+            env::predecessor_account_id() == self.owner,
+            "Only owner can call this function"
+        );
+
+        Promise::new(self.owner.clone()).transfer(amount.0)
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
@@ -0,0 +1,150 @@
+use near_contract_standards::fungible_token::events::{FtBurn, FtMint};
+use near_contract_standards::fungible_token::metadata::{
+    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+};
+use near_contract_standards::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Balance, Gas,
+    PanicOnDefault, Promise, PromiseError, PromiseOrValue, ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+// NEP-141 wrapped NEAR. Every token is backed 1:1 by NEAR held by the contract, so
+// `ft_total_supply` always equals the NEAR owed to the holders.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct WrappedNear {
+    token: FungibleToken,
+}
+
+#[near_bindgen]
+impl WrappedNear {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            token: FungibleToken::new(b"t".to_vec()),
+        }
+    }
+
+    // Deposit NEAR. Unregistered callers pay the storage of their balance out of the deposit.
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let mut deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+
+        if !self.token.accounts.contains_key(&caller) {
+            let min_balance = self.token.storage_balance_bounds().min.0;
+
+            self.token.internal_register_account(&caller);
+            deposit -= min_balance;
+
+            log!(format!("Registered {} for {}", caller, min_balance));
+        }
+
+        self.token.internal_deposit(&caller, deposit);
+
+        FtMint {
+            owner_id: &caller,
+            amount: &U128(deposit),
+            memo: Some("Wrapped NEAR deposit"),
+        }
+        .emit();
+    }
+
+    // Burns first, then transfers. `on_withdraw` mints the tokens back if the transfer fails.
+    #[payable]
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+
+        let caller = env::predecessor_account_id();
+
+        self.token.internal_withdraw(&caller, amount.0);
+
+        FtBurn {
+            owner_id: &caller,
+            amount: &amount,
+            memo: Some("Wrapped NEAR withdrawal"),
+        }
+        .emit();
+
+        // Attached yoctoNEAR is sent back together with the withdrawal
+        Promise::new(caller.clone()).transfer(amount.0 + 1).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas(5 * TGAS))
+                .on_withdraw(caller, amount),
+        )
+    }
+
+    #[private]
+    pub fn on_withdraw(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        if call_result.is_ok() {
+            return true;
+        }
+
+        // The account may have unregistered while the transfer was in flight. The NEAR stays
+        // in the contract without backing any tokens.
+        if !self.token.accounts.contains_key(&account) {
+            log!(format!(
+                "Withdraw of {} to {} failed. Account is not registered anymore",
+                amount.0, account
+            ));
+            return false;
+        }
+
+        log!(format!(
+            "Withdraw of {} to {} failed. Restoring balance",
+            amount.0, account
+        ));
+
+        self.token.internal_deposit(&account, amount.0);
+
+        FtMint {
+            owner_id: &account,
+            amount: &amount,
+            memo: Some("Wrapped NEAR withdrawal refund"),
+        }
+        .emit();
+
+        false
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.token.ft_balance_of(acc)
+    }
+
+    fn on_account_closed(&mut self, account_id: AccountId, balance: Balance) {
+        log!(format!("Closed @{} with {}", account_id, balance));
+    }
+
+    fn on_tokens_burned(&mut self, account_id: AccountId, amount: Balance) {
+        log!(format!("Account @{} burned {}", account_id, amount));
+    }
+}
+
+near_contract_standards::impl_fungible_token_core!(WrappedNear, token, on_tokens_burned);
+near_contract_standards::impl_fungible_token_storage!(WrappedNear, token, on_account_closed);
+
+#[near_bindgen]
+impl FungibleTokenMetadataProvider for WrappedNear {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Wrapped NEAR fungible token".to_string(),
+            symbol: "wNEAR".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use common::Payout;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::{
-    collections::UnorderedMap, env, json_types::U128, log, near_bindgen, require, AccountId,
-    Promise, ONE_NEAR,
+    collections::UnorderedMap, env, json_types::U128, log, near_bindgen, require, AccountId, Gas,
+    Promise, PromiseError, ONE_NEAR,
 };
 
+pub const TGAS: u64 = 1_000_000_000_000;
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct FungibleToken {
@@ -52,7 +55,7 @@ impl FungibleToken {
         self.internal_transfer_near(sender, receiver, amount)
     }
 
-    pub fn withdraw_near(&mut self, amount: U128) {
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
         let caller = env::predecessor_account_id();
         let near_deposit = self
             .user_near
@@ -66,8 +69,23 @@ impl FungibleToken {
             amount, near_deposit
         ));
 
-        Promise::new(caller.clone()).transfer(amount.0);
-        self.decrease_balance(caller, amount)
+        self.decrease_balance(caller.clone(), amount);
+
+        Promise::new(caller.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas(5 * TGAS))
+                .on_withdraw(caller, amount),
+        )
+    }
+
+    #[private]
+    pub fn on_withdraw(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        self.resolve_payout(call_result, account, amount)
     }
 
     pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
@@ -97,6 +115,21 @@ impl FungibleToken {
         log!(format!("Decreased {} of {}", new_deposit.0, account))
     }
 
+    fn increase_balance(&mut self, account: AccountId, amount: U128) {
+        let near_deposit = self.user_near.get(&account).unwrap_or(U128(0));
+
+        let new_deposit = U128::from(
+            near_deposit
+                .0
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str("Addition with overflow")),
+        );
+
+        self.user_near.insert(&account, &new_deposit);
+
+        log!(format!("Increased {} of {}", new_deposit.0, account))
+    }
+
     fn internal_transfer_near(&mut self, sender: AccountId, receiver: AccountId, amount: U128) {
         let sender_deposit = self
             .user_near
@@ -132,3 +165,9 @@ impl FungibleToken {
         ));
     }
 }
+
+impl Payout for FungibleToken {
+    fn payout_refund(&mut self, account: AccountId, amount: U128) {
+        self.increase_balance(account, amount)
+    }
+}
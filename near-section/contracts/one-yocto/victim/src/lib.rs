@@ -0,0 +1,103 @@
+use common::{Ownable, Ownership, Pausable, PauseState};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId,
+    PanicOnDefault, Promise, ONE_NEAR,
+};
+
+// VULNERABLE: privileged methods accept calls without an attached deposit, so they can be
+// signed with a function-call access key. Such keys are created for dApps with a gas
+// allowance and are often stored in the browser. A leaked key is enough to take the contract over.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Vault {
+    user_near: LookupMap<AccountId, U128>,
+    pause_state: PauseState,
+    ownership: Ownership,
+}
+
+#[near_bindgen]
+impl Vault {
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        Self {
+            user_near: LookupMap::new(b"u"),
+            pause_state: PauseState::new(b"p"),
+            ownership: Ownership::new(owner),
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        self.when_not_paused();
+
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 + deposit));
+
+        log!(format!("Added {} for {}", deposit, caller));
+    }
+
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
+        self.when_not_paused();
+
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        require!(amount <= near_deposit, "Not enough money");
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 - amount.0));
+
+        log!(format!("Transferred {} to {}", amount.0, caller));
+
+        Promise::new(caller).transfer(amount.0)
+    }
+
+    pub fn set_owner(&mut self, new_owner: AccountId) {
+        self.owner_set(new_owner)
+    }
+
+    pub fn pub_toggle_pause(&mut self) {
+        self.assert_owner();
+        self.toggle_pause()
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near.get(&acc).unwrap_or(U128(0))
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_get()
+    }
+
+    pub fn get_pause_status(&self) -> bool {
+        self.is_paused()
+    }
+}
+
+impl Ownable for Vault {
+    fn ownership(&self) -> &Ownership {
+        &self.ownership
+    }
+
+    fn ownership_mut(&mut self) -> &mut Ownership {
+        &mut self.ownership
+    }
+}
+
+impl Pausable for Vault {
+    fn pause_state(&self) -> &PauseState {
+        &self.pause_state
+    }
+
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause_state
+    }
+}
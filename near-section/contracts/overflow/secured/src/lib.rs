@@ -0,0 +1,69 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId,
+    PanicOnDefault,
+};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Token {
+    balances: LookupMap<AccountId, U128>,
+    total_supply: U128,
+}
+
+#[near_bindgen]
+impl Token {
+    #[init]
+    pub fn new(owner: AccountId, total_supply: U128) -> Self {
+        let mut balances = LookupMap::new(b"b");
+
+        balances.insert(&owner, &total_supply);
+
+        Self {
+            balances,
+            total_supply,
+        }
+    }
+
+    // Sends `amount` to every receiver. Checked arithmetic panics on overflow whatever
+    // profile the contract is built with.
+    pub fn batch_transfer(&mut self, receivers: Vec<AccountId>, amount: U128) {
+        let sender = env::predecessor_account_id();
+        let balance = self.view_balance(sender.clone());
+
+        let total = amount
+            .0
+            .checked_mul(receivers.len() as u128)
+            .unwrap_or_else(|| env::panic_str("Multiplication with overflow"));
+
+        require!(total <= balance.0, "Not enough balance");
+
+        self.balances.insert(&sender, &U128(balance.0 - total));
+
+        for receiver in receivers.iter() {
+            let receiver_balance = self.view_balance(receiver.clone());
+
+            let new_balance = receiver_balance
+                .0
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str("Addition with overflow"));
+
+            self.balances.insert(receiver, &U128(new_balance));
+        }
+
+        log!(format!(
+            "Sent {} to {} receivers from {}",
+            amount.0,
+            receivers.len(),
+            sender
+        ));
+    }
+
+    pub fn view_balance(&self, acc: AccountId) -> U128 {
+        self.balances.get(&acc).unwrap_or(U128(0))
+    }
+
+    pub fn total_supply(&self) -> U128 {
+        self.total_supply
+    }
+}
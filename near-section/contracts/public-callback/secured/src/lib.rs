@@ -0,0 +1,89 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId, Gas,
+    PanicOnDefault, Promise, PromiseError, ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Vault {
+    user_near: LookupMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Vault {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            user_near: LookupMap::new(b"u"),
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        self.increase_balance(env::predecessor_account_id(), U128(deposit));
+    }
+
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        require!(amount <= near_deposit, "Not enough money");
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 - amount.0));
+
+        Promise::new(caller.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas(5 * TGAS))
+                .on_withdraw(caller, amount),
+        )
+    }
+
+    // `#[private]` only accepts calls where predecessor is the contract itself, so the
+    // callback can only be reached through the promise created in `withdraw_near`.
+    #[private]
+    pub fn on_withdraw(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        if call_result.is_err() {
+            log!(format!(
+                "Withdraw of {} to {} failed. Restoring balance",
+                amount.0, account
+            ));
+
+            self.increase_balance(account, amount);
+            false
+        } else {
+            true
+        }
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near.get(&acc).unwrap_or(U128(0))
+    }
+
+    fn increase_balance(&mut self, account: AccountId, amount: U128) {
+        let near_deposit = self.view_near_deposit(account.clone());
+
+        let new_deposit = U128::from(
+            near_deposit
+                .0
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str("Addition with overflow")),
+        );
+
+        self.user_near.insert(&account, &new_deposit);
+
+        log!(format!("Increased {} of {}", new_deposit.0, account))
+    }
+}
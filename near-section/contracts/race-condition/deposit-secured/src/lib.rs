@@ -0,0 +1,225 @@
+use common::Payout;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::{LookupMap, UnorderedMap},
+    env, ext_contract,
+    json_types::U128,
+    log, near_bindgen, require, AccountId, Balance, Gas, PanicOnDefault, Promise, PromiseError,
+    ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+// Static gas of the staking call. `stake` settles rewards and updates the pool total and both
+// stake indexes. All unused gas goes to it as well.
+pub const STAKE_GAS: Gas = Gas(20 * TGAS);
+
+// Static gas of `resolve_staking`, which only updates the ledger
+pub const RESOLVE_STAKING_GAS: Gas = Gas(5 * TGAS);
+
+// Static gas of `on_withdraw`, which at most re-credits the ledger
+pub const ON_WITHDRAW_GAS: Gas = Gas(5 * TGAS);
+
+#[near_bindgen]
+#[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
+pub struct ReentrancyCheck {
+    user_near: UnorderedMap<AccountId, U128>,
+    // Funds of in-flight `stake` calls. They can not be staked or withdrawn a second time.
+    locked_near: LookupMap<AccountId, U128>,
+    staking_contract: AccountId,
+}
+#[ext_contract(staking)]
+trait Staking {
+    fn stake(&self, beneficiary: AccountId, validator: AccountId, amount: U128);
+}
+
+#[near_bindgen]
+impl ReentrancyCheck {
+    #[init]
+    pub fn new(staking_contract: AccountId) -> Self {
+        let user_near = UnorderedMap::new(b"u");
+        let locked_near = LookupMap::new(b"l");
+
+        Self {
+            user_near,
+            locked_near,
+            staking_contract,
+        }
+    }
+    // Deposit some cash
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+
+        self.increase_balance(caller, U128(deposit));
+    }
+
+    // Moves the amount to the locked balance before the cross-contract call. A second `stake`
+    // executed before `resolve_staking` only sees what is left. The NEAR is attached to the call,
+    // and refunded to this contract if staking fails.
+    pub fn stake(&mut self, validator: AccountId, amount: U128) -> Promise {
+        let beneficiary = env::predecessor_account_id();
+
+        let near_deposit = self
+            .user_near
+            .get(&beneficiary)
+            .unwrap_or_else(|| env::panic_str("User does not exist"));
+
+        require!(amount.0 != 0, "Amount should not be 0");
+        require!(amount <= near_deposit, "Not enough money");
+
+        self.decrease_balance(beneficiary.clone(), amount);
+        self.lock(&beneficiary, amount);
+
+        log!(format!(
+            "Inside deposit contract: Staked by {:?}, For {:?}, Amount {:?}",
+            beneficiary, validator, amount
+        ));
+
+        staking::ext(self.staking_contract.clone())
+            .with_static_gas(STAKE_GAS)
+            .with_unused_gas_weight(1)
+            .with_attached_deposit(amount.0)
+            .stake(beneficiary.clone(), validator, amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(RESOLVE_STAKING_GAS)
+                    .with_unused_gas_weight(0)
+                    .resolve_staking(amount, beneficiary),
+            )
+    }
+
+    pub fn get_current_balance(&self) -> Balance {
+        env::account_balance()
+    }
+
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
+        let caller = env::predecessor_account_id();
+        let near_deposit = self
+            .user_near
+            .get(&caller)
+            .unwrap_or_else(|| env::panic_str("User does not exist"));
+
+        require!(amount <= near_deposit, "Not enough money");
+
+        log!(format!(
+            "Transferred: {:?}. Current Deposit: {:?}",
+            amount, near_deposit
+        ));
+
+        self.decrease_balance(caller.clone(), amount);
+
+        Promise::new(caller.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(ON_WITHDRAW_GAS)
+                .on_withdraw(caller, amount),
+        )
+    }
+
+    #[private]
+    pub fn on_withdraw(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        self.resolve_payout(call_result, account, amount)
+    }
+
+    // Never panics: the locked amount is released on success and refunded on failure,
+    // so the ledger is consistent whatever the staking contract did.
+    #[private]
+    pub fn resolve_staking(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        amount: U128,
+        caller: AccountId,
+    ) -> bool {
+        self.unlock(&caller, amount);
+
+        if let Err(err) = call_result {
+            log!(format!("ERROR STAKING: {:?}. Refunding {}", err, amount.0));
+
+            self.increase_balance(caller, amount);
+            false
+        } else {
+            log!("ALL GOOD");
+            true
+        }
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near
+            .get(&acc)
+            .unwrap_or_else(|| env::panic_str("User does not exist"))
+    }
+
+    pub fn view_locked_deposit(&self, acc: AccountId) -> U128 {
+        self.locked_near.get(&acc).unwrap_or(U128(0))
+    }
+
+    fn decrease_balance(&mut self, account: AccountId, amount: U128) {
+        let near_deposit = self
+            .user_near
+            .get(&account)
+            .unwrap_or_else(|| env::panic_str("User does not exist"));
+
+        let new_deposit = U128::from(
+            near_deposit
+                .0
+                .checked_sub(amount.0)
+                .unwrap_or_else(|| env::panic_str("Subtract with underflow")),
+        );
+
+        self.user_near.insert(&account, &new_deposit);
+
+        log!(format!("Decreased {} of {}", new_deposit.0, account))
+    }
+
+    fn increase_balance(&mut self, account: AccountId, amount: U128) {
+        let near_deposit = self.user_near.get(&account).unwrap_or(U128(0));
+
+        let new_deposit = U128::from(
+            near_deposit
+                .0
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str("Addition with overflow")),
+        );
+
+        self.user_near.insert(&account, &new_deposit);
+
+        log!(format!("Added {} for {}", new_deposit.0, account))
+    }
+
+    fn lock(&mut self, account: &AccountId, amount: U128) {
+        let locked = self.view_locked_deposit(account.clone());
+
+        self.locked_near.insert(account, &U128(locked.0 + amount.0));
+    }
+
+    fn unlock(&mut self, account: &AccountId, amount: U128) {
+        let locked = self.view_locked_deposit(account.clone());
+
+        let new_locked = locked
+            .0
+            .checked_sub(amount.0)
+            .unwrap_or_else(|| env::panic_str("Subtract with underflow"));
+
+        if new_locked == 0 {
+            self.locked_near.remove(account);
+        } else {
+            self.locked_near.insert(account, &U128(new_locked));
+        }
+    }
+}
+
+impl Payout for ReentrancyCheck {
+    fn payout_refund(&mut self, account: AccountId, amount: U128) {
+        self.increase_balance(account, amount)
+    }
+}
@@ -1,3 +1,4 @@
+use common::Payout;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::{
     collections::UnorderedMap, env, ext_contract, json_types::U128, log, near_bindgen, require,
@@ -6,6 +7,16 @@ use near_sdk::{
 
 pub const TGAS: u64 = 1_000_000_000_000;
 
+// Static gas of the staking call. `stake` settles rewards and updates the pool total and both
+// stake indexes. All unused gas goes to it as well.
+pub const STAKE_GAS: Gas = Gas(20 * TGAS);
+
+// Static gas of `resolve_staking`, which only updates the ledger
+pub const RESOLVE_STAKING_GAS: Gas = Gas(5 * TGAS);
+
+// Static gas of `on_withdraw`, which at most re-credits the ledger
+pub const ON_WITHDRAW_GAS: Gas = Gas(5 * TGAS);
+
 #[near_bindgen]
 #[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
 pub struct ReentrancyCheck {
@@ -68,11 +79,14 @@ impl ReentrancyCheck {
         ));
 
         staking::ext(self.staking_contract.clone())
-            .with_static_gas(Gas(3 * TGAS))
+            .with_static_gas(STAKE_GAS)
+            .with_unused_gas_weight(1)
+            .with_attached_deposit(amount.0)
             .stake(beneficiary.clone(), validator.clone(), amount)
             .then(
                 Self::ext(env::current_account_id())
-                    .with_static_gas(Gas(3 * TGAS))
+                    .with_static_gas(RESOLVE_STAKING_GAS)
+                    .with_unused_gas_weight(0)
                     .resolve_staking(amount, beneficiary.clone()),
             );
     }
@@ -81,7 +95,7 @@ impl ReentrancyCheck {
         env::account_balance()
     }
 
-    pub fn withdraw_near(&mut self, amount: U128) {
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
         let caller = env::predecessor_account_id();
         let near_deposit = self
             .user_near
@@ -95,8 +109,23 @@ impl ReentrancyCheck {
             amount, near_deposit
         ));
 
-        Promise::new(caller.clone()).transfer(amount.0);
-        self.decrease_balance(caller, amount)
+        self.decrease_balance(caller.clone(), amount);
+
+        Promise::new(caller.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(ON_WITHDRAW_GAS)
+                .on_withdraw(caller, amount),
+        )
+    }
+
+    #[private]
+    pub fn on_withdraw(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        amount: U128,
+    ) -> bool {
+        self.resolve_payout(call_result, account, amount)
     }
     #[private]
     pub fn resolve_staking(
@@ -140,4 +169,25 @@ impl ReentrancyCheck {
 
         log!(format!("Decreased {} of {}", new_deposit.0, account))
     }
+
+    fn increase_balance(&mut self, account: AccountId, amount: U128) {
+        let near_deposit = self.user_near.get(&account).unwrap_or(U128(0));
+
+        let new_deposit = U128::from(
+            near_deposit
+                .0
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str("Addition with overflow")),
+        );
+
+        self.user_near.insert(&account, &new_deposit);
+
+        log!(format!("Increased {} of {}", new_deposit.0, account))
+    }
+}
+
+impl Payout for ReentrancyCheck {
+    fn payout_refund(&mut self, account: AccountId, amount: U128) {
+        self.increase_balance(account, amount)
+    }
 }
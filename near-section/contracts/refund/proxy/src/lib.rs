@@ -0,0 +1,34 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use near_sdk::ext_contract;
+use near_sdk::{json_types::U128, near_bindgen, AccountId, Gas, Promise};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[ext_contract(marketplace)]
+trait Marketplace {
+    fn register(&mut self);
+    fn buy(&mut self, item_id: String) -> Promise;
+}
+
+// Buys from its own balance on behalf of whoever calls it, as a DAO or a multisig would
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    pub fn register(market: AccountId, deposit: U128) -> Promise {
+        marketplace::ext(market)
+            .with_static_gas(Gas(10 * TGAS))
+            .with_attached_deposit(deposit.0)
+            .register()
+    }
+
+    pub fn buy(market: AccountId, item_id: String, deposit: U128) -> Promise {
+        marketplace::ext(market)
+            .with_static_gas(Gas(20 * TGAS))
+            .with_attached_deposit(deposit.0)
+            .buy(item_id)
+    }
+}
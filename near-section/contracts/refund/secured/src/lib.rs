@@ -0,0 +1,104 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{
+    collections::{LookupMap, LookupSet},
+    env,
+    json_types::U128,
+    log, near_bindgen, require, AccountId, Balance, PanicOnDefault, Promise, ONE_NEAR,
+};
+
+pub const REGISTRATION_FEE: Balance = ONE_NEAR / 10;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Listing {
+    seller: AccountId,
+    price: U128,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Marketplace {
+    members: LookupSet<AccountId>,
+    listings: LookupMap<String, Listing>,
+}
+
+#[near_bindgen]
+impl Marketplace {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            members: LookupSet::new(b"m"),
+            listings: LookupMap::new(b"l"),
+        }
+    }
+
+    // Everything attached above REGISTRATION_FEE is refunded
+    #[payable]
+    pub fn register(&mut self) {
+        require!(
+            env::attached_deposit() >= REGISTRATION_FEE,
+            "Not enough deposit"
+        );
+
+        let member = env::predecessor_account_id();
+
+        require!(self.members.insert(&member), "Already registered");
+
+        refund_surplus(REGISTRATION_FEE);
+
+        log!(format!("Registered {}", member));
+    }
+
+    pub fn list(&mut self, item_id: String, price: U128) {
+        let seller = env::predecessor_account_id();
+
+        require!(self.members.contains(&seller), "Not registered");
+        require!(!self.listings.contains_key(&item_id), "Already listed");
+
+        self.listings.insert(&item_id, &Listing { seller, price });
+    }
+
+    // Everything attached above the price is refunded
+    #[payable]
+    pub fn buy(&mut self, item_id: String) -> Promise {
+        let listing = self
+            .listings
+            .remove(&item_id)
+            .unwrap_or_else(|| env::panic_str("Not listed"));
+
+        require!(
+            env::attached_deposit() >= listing.price.0,
+            "Not enough deposit"
+        );
+
+        refund_surplus(listing.price.0);
+
+        log!(format!(
+            "{} bought {} for {}",
+            env::predecessor_account_id(),
+            item_id,
+            listing.price.0
+        ));
+
+        Promise::new(listing.seller).transfer(listing.price.0)
+    }
+
+    pub fn is_registered(&self, acc: AccountId) -> bool {
+        self.members.contains(&acc)
+    }
+
+    pub fn get_listing(&self, item_id: String) -> Option<Listing> {
+        self.listings.get(&item_id)
+    }
+}
+
+// Sends back what was attached above `required` to the account that attached it. The signer
+// may be a different account when the call comes from a contract.
+fn refund_surplus(required: Balance) {
+    let surplus = env::attached_deposit() - required;
+
+    if surplus > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(surplus);
+    }
+}
@@ -0,0 +1,95 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{
+    collections::{LookupMap, LookupSet},
+    env,
+    json_types::U128,
+    log, near_bindgen, require, AccountId, Balance, PanicOnDefault, Promise, ONE_NEAR,
+};
+
+pub const REGISTRATION_FEE: Balance = ONE_NEAR / 10;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Listing {
+    seller: AccountId,
+    price: U128,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Marketplace {
+    members: LookupSet<AccountId>,
+    listings: LookupMap<String, Listing>,
+}
+
+#[near_bindgen]
+impl Marketplace {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            members: LookupSet::new(b"m"),
+            listings: LookupMap::new(b"l"),
+        }
+    }
+
+    // VULNERABLE: everything attached above REGISTRATION_FEE stays in the contract
+    #[payable]
+    pub fn register(&mut self) {
+        require!(
+            env::attached_deposit() >= REGISTRATION_FEE,
+            "Not enough deposit"
+        );
+
+        let member = env::predecessor_account_id();
+
+        require!(self.members.insert(&member), "Already registered");
+
+        log!(format!("Registered {}", member));
+    }
+
+    pub fn list(&mut self, item_id: String, price: U128) {
+        let seller = env::predecessor_account_id();
+
+        require!(self.members.contains(&seller), "Not registered");
+        require!(!self.listings.contains_key(&item_id), "Already listed");
+
+        self.listings.insert(&item_id, &Listing { seller, price });
+    }
+
+    // VULNERABLE: the surplus goes to the signer of the transaction. When a contract buys,
+    // e.g. a DAO or a multisig, it paid the deposit but the surplus goes to whoever signed.
+    #[payable]
+    pub fn buy(&mut self, item_id: String) -> Promise {
+        let deposit = env::attached_deposit();
+        let listing = self
+            .listings
+            .remove(&item_id)
+            .unwrap_or_else(|| env::panic_str("Not listed"));
+
+        require!(deposit >= listing.price.0, "Not enough deposit");
+
+        let surplus = deposit - listing.price.0;
+
+        if surplus > 0 {
+            Promise::new(env::signer_account_id()).transfer(surplus);
+        }
+
+        log!(format!(
+            "{} bought {} for {}",
+            env::predecessor_account_id(),
+            item_id,
+            listing.price.0
+        ));
+
+        Promise::new(listing.seller).transfer(listing.price.0)
+    }
+
+    pub fn is_registered(&self, acc: AccountId) -> bool {
+        self.members.contains(&acc)
+    }
+
+    pub fn get_listing(&self, item_id: String) -> Option<Listing> {
+        self.listings.get(&item_id)
+    }
+}
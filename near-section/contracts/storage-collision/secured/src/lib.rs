@@ -0,0 +1,108 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId,
+    PanicOnDefault, Promise, ONE_NEAR,
+};
+
+mod storage;
+
+use storage::LedgerStorageKey;
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Ledger {
+    balances: LookupMap<AccountId, U128>,
+    // Owner -> spender -> amount the spender may move from the owner's balance
+    allowances: LookupMap<AccountId, LookupMap<AccountId, U128>>,
+}
+
+#[near_bindgen]
+impl Ledger {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            balances: LookupMap::new(LedgerStorageKey::Balances),
+            allowances: LookupMap::new(LedgerStorageKey::Allowances),
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+        let balance = self.view_balance(caller.clone());
+
+        self.balances.insert(&caller, &U128(balance.0 + deposit));
+
+        log!(format!("Added {} for {}", deposit, caller));
+    }
+
+    pub fn withdraw_near(&mut self, amount: U128) -> Promise {
+        let caller = env::predecessor_account_id();
+        let balance = self.view_balance(caller.clone());
+
+        require!(amount <= balance, "Not enough money");
+
+        self.balances.insert(&caller, &U128(balance.0 - amount.0));
+
+        log!(format!("Transferred {} to {}", amount.0, caller));
+
+        Promise::new(caller).transfer(amount.0)
+    }
+
+    pub fn approve(&mut self, spender: AccountId, amount: U128) {
+        let owner = env::predecessor_account_id();
+
+        let mut allowance = self.allowances.get(&owner).unwrap_or_else(|| {
+            LookupMap::new(LedgerStorageKey::Allowance {
+                owner: owner.clone(),
+            })
+        });
+
+        allowance.insert(&spender, &amount);
+        self.allowances.insert(&owner, &allowance);
+
+        log!(format!("{} approved {} for {}", owner, spender, amount.0));
+    }
+
+    pub fn transfer_from(&mut self, owner: AccountId, receiver: AccountId, amount: U128) {
+        let spender = env::predecessor_account_id();
+
+        let mut allowance = self
+            .allowances
+            .get(&owner)
+            .unwrap_or_else(|| env::panic_str("No allowance"));
+        let allowed = allowance.get(&spender).unwrap_or(U128(0));
+
+        require!(amount <= allowed, "Not enough allowance");
+
+        allowance.insert(&spender, &U128(allowed.0 - amount.0));
+        self.allowances.insert(&owner, &allowance);
+
+        let owner_balance = self.view_balance(owner.clone());
+
+        require!(amount <= owner_balance, "Not enough money");
+
+        self.balances
+            .insert(&owner, &U128(owner_balance.0 - amount.0));
+
+        let receiver_balance = self.view_balance(receiver.clone());
+
+        self.balances
+            .insert(&receiver, &U128(receiver_balance.0 + amount.0));
+    }
+
+    pub fn view_balance(&self, account: AccountId) -> U128 {
+        self.balances.get(&account).unwrap_or(U128(0))
+    }
+
+    pub fn view_allowance(&self, owner: AccountId, spender: AccountId) -> U128 {
+        self.allowances
+            .get(&owner)
+            .and_then(|allowance| allowance.get(&spender))
+            .unwrap_or(U128(0))
+    }
+}
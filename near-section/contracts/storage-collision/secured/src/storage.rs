@@ -0,0 +1,15 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    AccountId,
+};
+
+/// Defines a set of [`StorageKey`]s for the [`LookupMap`]'s prefixes.
+/// Every collection gets its own variant, so no two collections share a prefix.
+#[derive(Debug, Clone, BorshSerialize, near_sdk::BorshStorageKey)]
+pub(crate) enum LedgerStorageKey {
+    Balances,
+    Allowances,
+    // Nested map of a single owner. The owner is part of the prefix, so the maps of
+    // two owners never overlap.
+    Allowance { owner: AccountId },
+}
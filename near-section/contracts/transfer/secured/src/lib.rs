@@ -0,0 +1,92 @@
+use common::Payout;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId, Gas,
+    PanicOnDefault, Promise, PromiseError, ONE_NEAR,
+};
+
+pub const TGAS: u64 = 1_000_000_000_000;
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Payouts {
+    user_near: LookupMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Payouts {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            user_near: LookupMap::new(b"u"),
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 + deposit));
+
+        log!(format!("Added {} for {}", deposit, caller));
+    }
+
+    // Pays `amount` out of the caller's deposit to any account. A receiver that does not exist
+    // and can not be created by a plain transfer refunds the NEAR, and `on_transfer` puts it
+    // back into the caller's deposit.
+    pub fn withdraw_to(&mut self, receiver: AccountId, amount: U128) -> Promise {
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        require!(amount.0 != 0, "Amount should not be 0");
+        require!(amount <= near_deposit, "Not enough money");
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 - amount.0));
+
+        log!(format!(
+            "Sending {} from {} to {}",
+            amount.0, caller, receiver
+        ));
+
+        Promise::new(receiver.clone()).transfer(amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas(5 * TGAS))
+                .on_transfer(caller, receiver, amount),
+        )
+    }
+
+    #[private]
+    pub fn on_transfer(
+        &mut self,
+        #[callback_result] call_result: Result<(), PromiseError>,
+        account: AccountId,
+        receiver: AccountId,
+        amount: U128,
+    ) -> bool {
+        if call_result.is_err() {
+            log!(format!("Transfer to {} failed", receiver));
+        }
+
+        self.resolve_payout(call_result, account, amount)
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near.get(&acc).unwrap_or(U128(0))
+    }
+}
+
+impl Payout for Payouts {
+    fn payout_refund(&mut self, account: AccountId, amount: U128) {
+        let near_deposit = self.view_near_deposit(account.clone());
+
+        self.user_near
+            .insert(&account, &U128(near_deposit.0 + amount.0));
+    }
+}
@@ -0,0 +1,63 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{
+    collections::LookupMap, env, json_types::U128, log, near_bindgen, require, AccountId,
+    PanicOnDefault, Promise, ONE_NEAR,
+};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Payouts {
+    user_near: LookupMap<AccountId, U128>,
+}
+
+#[near_bindgen]
+impl Payouts {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            user_near: LookupMap::new(b"u"),
+        }
+    }
+
+    #[payable]
+    pub fn deposit_near(&mut self) {
+        let deposit = env::attached_deposit();
+
+        require!(deposit >= ONE_NEAR, "Not enough deposit");
+
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 + deposit));
+
+        log!(format!("Added {} for {}", deposit, caller));
+    }
+
+    // Pays `amount` out of the caller's deposit to any account.
+    //
+    // VULNERABLE: a transfer to a named account that does not exist fails, and so does one
+    // creating an implicit account with less than its storage costs. The NEAR is refunded
+    // to this contract, but the deposit was already debited and nobody can withdraw it.
+    pub fn withdraw_to(&mut self, receiver: AccountId, amount: U128) -> Promise {
+        let caller = env::predecessor_account_id();
+        let near_deposit = self.view_near_deposit(caller.clone());
+
+        require!(amount.0 != 0, "Amount should not be 0");
+        require!(amount <= near_deposit, "Not enough money");
+
+        self.user_near
+            .insert(&caller, &U128(near_deposit.0 - amount.0));
+
+        log!(format!(
+            "Sending {} from {} to {}",
+            amount.0, caller, receiver
+        ));
+
+        Promise::new(receiver).transfer(amount.0)
+    }
+
+    pub fn view_near_deposit(&self, acc: AccountId) -> U128 {
+        self.user_near.get(&acc).unwrap_or(U128(0))
+    }
+}
@@ -0,0 +1,103 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{collections::UnorderedMap, env, near_bindgen, require, AccountId, PanicOnDefault};
+
+pub const DEFAULT_MAX_MESSAGE_LENGTH: u32 = 280;
+
+// Version of the state layout, bumped by every migration
+pub const STATE_VERSION: u32 = 2;
+
+// Key near-sdk stores the contract state under
+const STATE_KEY: &[u8] = b"STATE";
+
+// Layout of v1, used to read the state left by the previous code
+#[derive(BorshDeserialize)]
+pub struct OldStatusMessage {
+    owner: AccountId,
+    messages: UnorderedMap<AccountId, String>,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    version: u32,
+    owner: AccountId,
+    messages: UnorderedMap<AccountId, String>,
+    max_message_length: u32,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        Self {
+            version: STATE_VERSION,
+            owner,
+            messages: UnorderedMap::new(b"m"),
+            max_message_length: DEFAULT_MAX_MESSAGE_LENGTH,
+        }
+    }
+
+    // Has to be called in the same batch as the deployment of the new code, so no other
+    // method runs against the old state. Collections keep their prefix, their entries are
+    // not touched. State already in the current layout is rejected by its version.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state =
+            env::storage_read(STATE_KEY).unwrap_or_else(|| env::panic_str("No state to migrate"));
+
+        if let Ok(current) = Self::try_from_slice(&state) {
+            require!(current.version < STATE_VERSION, "Already migrated");
+        }
+
+        let old_state = OldStatusMessage::try_from_slice(&state)
+            .unwrap_or_else(|_| env::panic_str("Cannot parse the old state"));
+
+        Self {
+            version: STATE_VERSION,
+            owner: old_state.owner,
+            messages: old_state.messages,
+            max_message_length: DEFAULT_MAX_MESSAGE_LENGTH,
+        }
+    }
+
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        let caller = env::predecessor_account_id();
+
+        require!(
+            message.len() <= self.max_message_length as usize,
+            "Message is too long"
+        );
+
+        self.messages.insert(&caller, &message);
+    }
+
+    pub fn set_max_message_length(&mut self, max_message_length: u32) {
+        require!(
+            env::predecessor_account_id() == self.owner,
+            "Only owner can call this function"
+        );
+
+        self.max_message_length = max_message_length;
+    }
+
+    pub fn get_message(&self, account: AccountId) -> Option<String> {
+        self.messages.get(&account)
+    }
+
+    pub fn get_messages_count(&self) -> u64 {
+        self.messages.len()
+    }
+
+    pub fn get_max_message_length(&self) -> u32 {
+        self.max_message_length
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
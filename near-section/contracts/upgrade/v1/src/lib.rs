@@ -0,0 +1,39 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{collections::UnorderedMap, env, near_bindgen, AccountId, PanicOnDefault};
+
+// First version of the contract. Its Borsh layout is what v2 finds in storage after an upgrade.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    owner: AccountId,
+    messages: UnorderedMap<AccountId, String>,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        Self {
+            owner,
+            messages: UnorderedMap::new(b"m"),
+        }
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        let caller = env::predecessor_account_id();
+
+        self.messages.insert(&caller, &message);
+    }
+
+    pub fn get_message(&self, account: AccountId) -> Option<String> {
+        self.messages.get(&account)
+    }
+
+    pub fn get_messages_count(&self) -> u64 {
+        self.messages.len()
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
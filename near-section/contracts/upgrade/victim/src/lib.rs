@@ -0,0 +1,63 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{collections::UnorderedMap, env, near_bindgen, require, AccountId, PanicOnDefault};
+
+pub const DEFAULT_MAX_MESSAGE_LENGTH: u32 = 280;
+
+// VULNERABLE: v2 adds `max_message_length` to the root struct, but is deployed over v1 state
+// without a migration. The stored state has no bytes for the new field, so it can not be
+// deserialized and every method that touches state panics.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StatusMessage {
+    owner: AccountId,
+    messages: UnorderedMap<AccountId, String>,
+    max_message_length: u32,
+}
+
+#[near_bindgen]
+impl StatusMessage {
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        Self {
+            owner,
+            messages: UnorderedMap::new(b"m"),
+            max_message_length: DEFAULT_MAX_MESSAGE_LENGTH,
+        }
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        let caller = env::predecessor_account_id();
+
+        require!(
+            message.len() <= self.max_message_length as usize,
+            "Message is too long"
+        );
+
+        self.messages.insert(&caller, &message);
+    }
+
+    pub fn set_max_message_length(&mut self, max_message_length: u32) {
+        require!(
+            env::predecessor_account_id() == self.owner,
+            "Only owner can call this function"
+        );
+
+        self.max_message_length = max_message_length;
+    }
+
+    pub fn get_message(&self, account: AccountId) -> Option<String> {
+        self.messages.get(&account)
+    }
+
+    pub fn get_messages_count(&self) -> u64 {
+        self.messages.len()
+    }
+
+    pub fn get_max_message_length(&self) -> u32 {
+        self.max_message_length
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
